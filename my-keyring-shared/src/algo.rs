@@ -1,7 +1,7 @@
 use hmac::{Hmac, Mac, NewMac};
 use serde::{Deserialize, Serialize};
 use sha1::Sha1;
-use sha2::{Sha256, Sha512};
+use sha2::{Digest, Sha256, Sha512};
 
 use crate::errors::MyKeyringError;
 
@@ -17,6 +17,52 @@ pub enum Algorithm {
 }
 
 impl Algorithm {
+    /// Length, in bytes, of the hmac digest
+    ///
+    /// It is also the recommended length of the hmac secret, RFC 2104 advising
+    /// against keys shorter than the digest.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use my_keyring_shared::Algorithm;
+    ///
+    /// assert_eq!(Algorithm::Sha256.digest_length(), 32);
+    /// ```
+    #[inline]
+    pub fn digest_length(self) -> usize {
+        match self {
+            Self::Sha1 => 20,
+            Self::Sha256 => 32,
+            Self::Sha512 => 64,
+        }
+    }
+
+    /// Compute the hash of `data`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use my_keyring_shared::Algorithm;
+    ///
+    /// let digest = Algorithm::Sha1.hash(b"1234");
+    /// assert_eq!(
+    ///     &digest,
+    ///     &[
+    ///         113, 16, 237, 164, 208, 158, 6, 42, 165, 228, 163, 144, 176, 165, 114, 172, 13, 44, 2,
+    ///         32
+    ///     ]
+    /// );
+    /// ```
+    #[inline]
+    pub fn hash(self, data: &[u8]) -> Vec<u8> {
+        match self {
+            Self::Sha1 => Sha1::digest(data).to_vec(),
+            Self::Sha256 => Sha256::digest(data).to_vec(),
+            Self::Sha512 => Sha512::digest(data).to_vec(),
+        }
+    }
+
     /// Compute the hmac of `data` using a `key` secret
     ///
     /// # Examples
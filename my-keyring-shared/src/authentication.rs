@@ -1,31 +1,195 @@
-use fnv::FnvHashMap;
+//! Contains the `Authentication`, the login information of an account
+//!
+//! # Examples
+//!
+//! ```
+//! use my_keyring_shared::{
+//!     authentication::{Authentication, CustomField},
+//!     tag::Tags,
+//! };
+//! use ulid::Ulid;
+//!
+//! let mut login = Authentication::new("ACME", "john.doe", "My_secr3tP@55w0rd", "");
+//! login.set_field("PIN", CustomField::Hidden("1234".into()));
+//! login.add_tag(Ulid::new());
+//!
+//! assert_eq!(login.password(), "My_secr3tP@55w0rd");
+//! assert!(login.modified() >= login.created());
+//! ```
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
 use ulid::Ulid;
 
-use crate::tag::Tags;
+pub(crate) use self::field::days_in_month;
+pub use self::field::{CustomField, Date};
+use crate::{secret::SecretString, tag::Tags};
+
+mod field;
 
-#[derive(Debug)]
+/// Login information of an account
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
 pub struct Authentication {
+    /// Identifier, also giving the creation time
     id: Ulid,
+    /// Generated at each modification, giving its time
+    modified: Ulid,
     name: String,
     username: String,
-    password: String,
+    password: SecretString,
     notes: String,
     tags: Vec<Ulid>,
-    additional_field: FnvHashMap<String, String>,
+    /// Additional fields, by name, in the display order
+    additional_field: Vec<(String, CustomField)>,
 }
 
 impl Authentication {
+    /// Create the login information of the account `name`
     pub fn new(name: &str, username: &str, password: &str, notes: &str) -> Self {
+        let id = Ulid::new();
+
         Self {
-            id: Ulid::new(),
+            id,
+            modified: id,
             name: name.to_owned(),
             username: username.to_owned(),
-            password: password.to_owned(),
+            password: password.into(),
             notes: notes.to_owned(),
             tags: Vec::new(),
             additional_field: Default::default(),
         }
     }
+
+    /// Return the identifier
+    #[inline]
+    pub fn id(&self) -> Ulid {
+        self.id
+    }
+
+    /// Return the creation time, taken from the identifier
+    #[inline]
+    pub fn created(&self) -> SystemTime {
+        ulid_time(self.id)
+    }
+
+    /// Return the time of the last modification
+    #[inline]
+    pub fn modified(&self) -> SystemTime {
+        ulid_time(self.modified)
+    }
+
+    /// Return the name of the account, like the website
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Rename the account
+    pub fn set_name(&mut self, name: &str) {
+        self.name = name.to_owned();
+        self.touch();
+    }
+
+    /// Return the username or email used to log in
+    #[inline]
+    pub fn username(&self) -> &str {
+        &self.username
+    }
+
+    /// Change the username or email used to log in
+    pub fn set_username(&mut self, username: &str) {
+        self.username = username.to_owned();
+        self.touch();
+    }
+
+    /// Return the password
+    #[inline]
+    pub fn password(&self) -> &str {
+        &self.password
+    }
+
+    /// Change the password, the previous one being wiped from memory
+    pub fn set_password(&mut self, password: &str) {
+        self.password = password.into();
+        self.touch();
+    }
+
+    /// Return the free notes
+    #[inline]
+    pub fn notes(&self) -> &str {
+        &self.notes
+    }
+
+    /// Replace the free notes
+    pub fn set_notes(&mut self, notes: &str) {
+        self.notes = notes.to_owned();
+        self.touch();
+    }
+
+    /// Return the identifiers of the tags, see [`crate::tag::TagPool`]
+    #[inline]
+    pub fn tag_ids(&self) -> &[Ulid] {
+        &self.tags
+    }
+
+    /// Return the additional field `name`
+    pub fn field(&self, name: &str) -> Option<&CustomField> {
+        self.field_position(name)
+            .map(|position| &self.additional_field[position].1)
+    }
+
+    /// List the additional fields, with their names, in the display order
+    pub fn fields(&self) -> impl Iterator<Item = (&str, &CustomField)> {
+        self.additional_field
+            .iter()
+            .map(|(name, value)| (name.as_str(), value))
+    }
+
+    /// Replace the additional field `name`, keeping its position, or add it
+    /// after the others
+    pub fn set_field(&mut self, name: &str, value: CustomField) {
+        match self.field_position(name) {
+            Some(position) => self.additional_field[position].1 = value,
+            None => self.additional_field.push((name.to_owned(), value)),
+        }
+        self.touch();
+    }
+
+    /// Move the additional field `name` at the `position` of the display
+    /// order, the last one if out of range, returning if it was present
+    pub fn move_field(&mut self, name: &str, position: usize) -> bool {
+        match self.field_position(name) {
+            Some(current) => {
+                let field = self.additional_field.remove(current);
+                let position = position.min(self.additional_field.len());
+                self.additional_field.insert(position, field);
+                self.touch();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Remove the additional field `name`, returning it if it was present
+    pub fn remove_field(&mut self, name: &str) -> Option<CustomField> {
+        let position = self.field_position(name)?;
+        self.touch();
+        Some(self.additional_field.remove(position).1)
+    }
+
+    /// Return the position of the additional field `name`
+    fn field_position(&self, name: &str) -> Option<usize> {
+        self.additional_field
+            .iter()
+            .position(|(field, _)| field == name)
+    }
+
+    /// Record a modification, now
+    fn touch(&mut self) {
+        self.modified = Ulid::new();
+    }
 }
 
 impl Tags for Authentication {
@@ -33,4 +197,82 @@ impl Tags for Authentication {
     fn tags(&mut self) -> &mut Vec<Ulid> {
         &mut self.tags
     }
+
+    #[inline]
+    fn tags_changed(&mut self) {
+        self.touch();
+    }
+}
+
+/// Return the time a `ulid` has been generated
+pub(crate) fn ulid_time(ulid: Ulid) -> SystemTime {
+    UNIX_EPOCH + Duration::from_millis(ulid.timestamp_ms())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accessors_record_modifications() {
+        let mut login = Authentication::new("ACME", "john.doe", "password", "notes");
+        assert_eq!(login.created(), login.modified());
+        let modified = login.modified;
+
+        login.set_username("jane.doe");
+        login.set_password("My_secr3tP@55w0rd");
+        assert_eq!(login.username(), "jane.doe");
+        assert_eq!(login.password(), "My_secr3tP@55w0rd");
+        assert_ne!(login.modified, modified);
+        assert!(login.modified() >= login.created());
+
+        let tag = Ulid::new();
+        login.add_tag(tag);
+        let modified = login.modified;
+        login.add_tag(tag);
+        login.del_tag(Ulid::new());
+        assert_eq!(login.tag_ids(), [tag]);
+        // Nothing changed
+        assert_eq!(login.modified, modified);
+
+        // Never printed
+        assert!(!format!("{:?}", login).contains("P@55"));
+    }
+
+    #[test]
+    fn custom_fields() {
+        let mut login = Authentication::new("ACME", "john.doe", "password", "");
+        login.set_field("PIN", CustomField::Hidden("1234".into()));
+        login.set_field("Question", CustomField::Text("Blue".to_owned()));
+
+        assert_eq!(
+            login.field("PIN"),
+            Some(&CustomField::Hidden("1234".into()))
+        );
+        assert_eq!(login.fields().count(), 2);
+
+        // Through the serialised form
+        let mut login: Authentication =
+            bincode::deserialize(&bincode::serialize(&login).unwrap()).unwrap();
+        assert_eq!(
+            login.field("Question"),
+            Some(&CustomField::Text("Blue".to_owned()))
+        );
+
+        // Replaced in place, then reordered
+        login.set_field("PIN", CustomField::Hidden("0000".into()));
+        login.set_field("Site", CustomField::Url("https://example.com".to_owned()));
+        let names = |login: &Authentication| -> Vec<String> {
+            login.fields().map(|(name, _)| name.to_owned()).collect()
+        };
+        assert_eq!(names(&login), ["PIN", "Question", "Site"]);
+        assert!(login.move_field("Site", 0));
+        assert!(login.move_field("PIN", 10));
+        assert!(!login.move_field("Unknown", 0));
+        assert_eq!(names(&login), ["Site", "Question", "PIN"]);
+
+        assert!(login.remove_field("PIN").is_some());
+        assert!(login.remove_field("PIN").is_none());
+        assert_eq!(login.fields().count(), 2);
+    }
 }
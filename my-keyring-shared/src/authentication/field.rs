@@ -0,0 +1,268 @@
+use core::{convert::TryFrom, fmt};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use ulid::Ulid;
+
+use crate::{secret::SecretString, totp::Totp, MyKeyringError};
+
+/// Value of an additional field of an `Authentication`
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> my_keyring_shared::Result<()> {
+/// use my_keyring_shared::{
+///     authentication::{CustomField, Date},
+///     totp::Totp,
+/// };
+///
+/// let fields = [
+///     CustomField::Hidden("1234".into()),
+///     CustomField::Date(Date::new(2021, 4, 30)?),
+///     CustomField::Totp(Totp::new("JBSWY3DPEB3W64TMMQQQ", None, None, None)?),
+/// ];
+///
+/// for field in &fields {
+///     match field {
+///         _ if field.is_concealed() => println!("••••••"),
+///         CustomField::Totp(totp) => println!("{}", totp.totp()),
+///         _ => println!("{}", field.as_text().unwrap_or_default()),
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub enum CustomField {
+    /// Free text, displayed as is
+    Text(String),
+    /// Sensitive value, masked until revealed
+    Hidden(SecretString),
+    /// Web address
+    Url(String),
+    /// Email address
+    Email(String),
+    /// Phone number
+    Phone(String),
+    /// Calendar date
+    Date(Date),
+    /// One-time password generator, its code rendered live
+    Totp(Totp),
+    /// Reference to the field `field` of the entry `entry`, not copied
+    Linked {
+        /// Identifier of the entry holding the field
+        entry: Ulid,
+        /// Name of the field in this entry
+        field: String,
+    },
+}
+
+impl CustomField {
+    /// Return if the value must be masked until revealed by the user
+    #[inline]
+    pub fn is_concealed(&self) -> bool {
+        matches!(self, Self::Hidden(_))
+    }
+
+    /// Return the value of the textual fields, including the hidden ones
+    ///
+    /// The dates are not text, see [`Date`] for their rendering.
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            Self::Text(text) | Self::Url(text) | Self::Email(text) | Self::Phone(text) => {
+                Some(text)
+            }
+            Self::Hidden(secret) => Some(secret),
+            Self::Date(_) | Self::Totp(_) | Self::Linked { .. } => None,
+        }
+    }
+}
+
+/// A calendar date, without time zone
+///
+/// It is rendered as ISO 8601, `YYYY-MM-DD`, and dates are ordered
+/// chronologically.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase", try_from = "WireDate")]
+pub struct Date {
+    year: u16,
+    month: u8,
+    day: u8,
+}
+
+/// Serialised layout of a [`Date`], checked by [`Date::new`] once read
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+struct WireDate {
+    year: u16,
+    month: u8,
+    day: u8,
+}
+
+impl TryFrom<WireDate> for Date {
+    type Error = &'static str;
+
+    fn try_from(date: WireDate) -> Result<Self, Self::Error> {
+        Self::new(date.year, date.month, date.day).map_err(|_| "invalid date")
+    }
+}
+
+impl Date {
+    /// Create the date `year`-`month`-`day`, `month` and `day` starting at 1
+    ///
+    /// # Errors
+    ///
+    /// Return [`MyKeyringError::InvalidDate`] if the date does not exist, like
+    /// the February 29 of a non leap year, or if the `year` has more than 4
+    /// digits
+    pub fn new(year: u16, month: u8, day: u8) -> crate::Result<Self> {
+        if year > 9999 || day == 0 || day > days_in_month(year, month) {
+            return Err(MyKeyringError::InvalidDate);
+        }
+
+        Ok(Self { year, month, day })
+    }
+
+    /// Return the current date, in UTC
+    pub fn today() -> Self {
+        Self::from_system_time(SystemTime::now())
+    }
+
+    /// Return the date of the `time`, in UTC
+    ///
+    /// The times before 1970 give 1970-01-01.
+    pub fn from_system_time(time: SystemTime) -> Self {
+        let days = time
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs() / 86_400);
+
+        // Days to civil date, from "chrono-Compatible Low-Level Date
+        // Algorithms", with years starting in March
+        let days = days + 719_468;
+        let era = days / 146_097;
+        let day_of_era = days % 146_097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        };
+        let year = year_of_era + era * 400 + u64::from(month <= 2);
+
+        Self {
+            year: u16::try_from(year).unwrap_or(9999).min(9999),
+            month: month as u8,
+            day: day as u8,
+        }
+    }
+
+    /// Return the year
+    #[inline]
+    pub fn year(&self) -> u16 {
+        self.year
+    }
+
+    /// Return the month, from 1 to 12
+    #[inline]
+    pub fn month(&self) -> u8 {
+        self.month
+    }
+
+    /// Return the day of the month, from 1
+    #[inline]
+    pub fn day(&self) -> u8 {
+        self.day
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// Return the number of days of the `month` of the `year`, 0 if the month is
+/// invalid
+pub(crate) fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400)) => {
+            29
+        }
+        2 => 28,
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dates() {
+        assert_eq!(Date::new(2021, 4, 30).unwrap().to_string(), "2021-04-30");
+        assert!(Date::new(2020, 2, 29).is_ok());
+        assert!(Date::new(2000, 2, 29).is_ok());
+
+        for (year, month, day) in [
+            (2021, 2, 29),
+            (1900, 2, 29),
+            (2021, 4, 31),
+            (2021, 13, 1),
+            (2021, 1, 0),
+        ] {
+            assert_eq!(
+                Date::new(year, month, day).unwrap_err(),
+                MyKeyringError::InvalidDate
+            );
+        }
+
+        assert!(Date::new(2020, 12, 31).unwrap() < Date::new(2021, 1, 1).unwrap());
+
+        // Checked when read too
+        let read = |year: u16, month: u8, day: u8| -> Option<Date> {
+            bincode::deserialize(&bincode::serialize(&(year, month, day)).unwrap()).ok()
+        };
+        assert_eq!(read(2020, 2, 29), Date::new(2020, 2, 29).ok());
+        assert_eq!(read(2021, 2, 31), None);
+        assert_eq!(read(2021, 0, 1), None);
+    }
+
+    #[test]
+    fn dates_from_time() {
+        use std::time::Duration;
+
+        let date = |secs| Date::from_system_time(UNIX_EPOCH + Duration::from_secs(secs));
+        assert_eq!(date(0).to_string(), "1970-01-01");
+        // 2009-02-13 23:31:30
+        assert_eq!(date(1_234_567_890).to_string(), "2009-02-13");
+        // 2000-02-29 and 2100-03-01
+        assert_eq!(date(951_782_400).to_string(), "2000-02-29");
+        assert_eq!(date(4_107_542_400).to_string(), "2100-03-01");
+        assert!(Date::today() > date(1_234_567_890));
+    }
+
+    #[test]
+    fn concealed_and_text() {
+        let hidden = CustomField::Hidden("1234".into());
+        assert!(hidden.is_concealed());
+        assert_eq!(hidden.as_text(), Some("1234"));
+
+        let url = CustomField::Url("https://example.com".to_owned());
+        assert!(!url.is_concealed());
+        assert_eq!(url.as_text(), Some("https://example.com"));
+
+        let linked = CustomField::Linked {
+            entry: Ulid::new(),
+            field: "PIN".to_owned(),
+        };
+        assert_eq!(linked.as_text(), None);
+    }
+}
@@ -1,26 +1,146 @@
-use core::convert::TryFrom;
+use core::convert::{TryFrom, TryInto};
 
-use bincode::{deserialize, serialize};
+use bincode::deserialize;
 use serde::{Deserialize, Serialize};
 
-use crate::{crypt::Salt, MyKeyringError};
+use crate::{
+    crypt::{Kdf, Salt, SALT_LENGTH},
+    MyKeyringError,
+};
+
+/// Magic number at the beginning of every versioned message
+///
+/// Unversioned messages start with a random salt, so there is a 2^-32 chance
+/// for them to start with it too: they are read as unversioned ones when they
+/// cannot be read as versioned ones.
+const MAGIC: &[u8; 4] = b"MKCM";
+/// Current version of the message format
+const VERSION: u8 = 1;
+/// XChaCha20-Poly1305, with the key and nonce derived by HKDF-SHA512
+const CIPHER_XCHACHA20_POLY1305: u8 = 1;
+/// No password, the secret is used directly
+const KDF_NONE: u8 = 0;
+/// PBKDF2-HMAC-SHA512, followed by the iterations
+const KDF_PBKDF2: u8 = 1;
+/// Argon2id, followed by the memory, iterations and parallelism
+const KDF_ARGON2ID: u8 = 2;
 
 /// Represent a message encrypted, and the information needed to decrypt it
-#[derive(Serialize, Deserialize)]
+///
+/// # Wire format
+///
+/// | Field   | Length | Content                                          |
+/// |---------|--------|--------------------------------------------------|
+/// | magic   | 4      | `MKCM`                                           |
+/// | version | 1      | `1`                                              |
+/// | cipher  | 1      | `1`: XChaCha20-Poly1305                          |
+/// | kdf     | 1      | `0`: none, `1`: PBKDF2, `2`: Argon2id            |
+/// | params  | 0..12  | big endian `u32` parameters of the kdf           |
+/// | salt    | 16     | salt of the key derivation                       |
+/// | data    | ..     | encrypted data, followed by the 16 bytes tag     |
+///
+/// Messages without the magic number are read as the legacy version 0, the
+/// bincode serialisation of this structure.
+#[derive(Clone, Serialize, Deserialize)]
 pub struct CryptedMessage {
     /// Public salt to derive passwords/iv for encryption/hmac
     pub(crate) salt: Salt,
     /// Data encrypted
     pub(crate) data: Vec<u8>,
+    /// Parameters used to stretch the password, if encrypted with a password
+    pub(crate) kdf: Option<Kdf>,
+}
+
+/// Layout of the version 0 messages written before the key derivation function
+/// was stored
+#[derive(Deserialize)]
+struct LegacyCryptedMessage {
+    salt: Salt,
+    data: Vec<u8>,
 }
 
 opaque_debug::implement!(CryptedMessage);
 
+impl CryptedMessage {
+    /// Read an unversioned, version 0, message
+    fn read_v0(value: &[u8]) -> crate::Result<Self> {
+        let message: Self = deserialize(value)
+            .or_else(|_| {
+                // The first messages end just before the kdf field
+                deserialize::<LegacyCryptedMessage>(value).map(|legacy| Self {
+                    salt: legacy.salt,
+                    data: legacy.data,
+                    kdf: None,
+                })
+            })
+            .map_err(|_| MyKeyringError::InvalidCryptedMessage)?;
+        message.kdf.map(Kdf::check).transpose()?;
+
+        Ok(message)
+    }
+
+    /// Read a version 1 message, `value` starting after the version
+    fn read_v1(mut value: &[u8]) -> crate::Result<Self> {
+        if take(&mut value, 1)?[0] != CIPHER_XCHACHA20_POLY1305 {
+            return Err(MyKeyringError::UnsupportedAlgorithm);
+        }
+        let kdf = match take(&mut value, 1)?[0] {
+            KDF_NONE => None,
+            KDF_PBKDF2 => Some(Kdf::Pbkdf2 {
+                iterations: take_u32(&mut value)?,
+            }),
+            KDF_ARGON2ID => Some(Kdf::Argon2id {
+                memory: take_u32(&mut value)?,
+                iterations: take_u32(&mut value)?,
+                parallelism: take_u32(&mut value)?,
+            }),
+            _ => return Err(MyKeyringError::UnsupportedAlgorithm),
+        };
+        kdf.map(Kdf::check).transpose()?;
+        let salt = take(&mut value, SALT_LENGTH)?
+            .try_into()
+            .expect("salt length");
+
+        Ok(Self {
+            salt,
+            data: value.to_vec(),
+            kdf,
+        })
+    }
+}
+
+/// Extract the `len` first bytes of `value`, advancing it
+fn take<'a>(value: &mut &'a [u8], len: usize) -> crate::Result<&'a [u8]> {
+    if value.len() < len {
+        return Err(MyKeyringError::InvalidCryptedMessage);
+    }
+    let (head, tail) = value.split_at(len);
+    *value = tail;
+    Ok(head)
+}
+
+/// Extract a big endian `u32` from `value`, advancing it
+fn take_u32(value: &mut &[u8]) -> crate::Result<u32> {
+    Ok(u32::from_be_bytes(
+        take(value, 4)?.try_into().expect("u32 length"),
+    ))
+}
+
 impl TryFrom<&[u8]> for CryptedMessage {
     type Error = MyKeyringError;
 
     fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
-        deserialize(value).map_err(|_| MyKeyringError::InvalidCryptedMessage)
+        if !value.starts_with(MAGIC) {
+            return Self::read_v0(value);
+        }
+
+        let mut versioned = &value[MAGIC.len()..];
+        let message = match take(&mut versioned, 1)?[0] {
+            VERSION => Self::read_v1(versioned),
+            _ => Err(MyKeyringError::UnsupportedCryptedMessageVersion),
+        };
+        // A legacy message whose random salt starts with the magic number
+        message.or_else(|error| Self::read_v0(value).map_err(|_| error))
     }
 }
 
@@ -32,8 +152,187 @@ impl TryFrom<Vec<u8>> for CryptedMessage {
     }
 }
 
-impl Into<Vec<u8>> for CryptedMessage {
-    fn into(self) -> Vec<u8> {
-        serialize(&self).expect("serialized CryptedMessage struct data")
+impl From<CryptedMessage> for Vec<u8> {
+    fn from(message: CryptedMessage) -> Self {
+        let mut data = Vec::with_capacity(MAGIC.len() + 3 + 12 + SALT_LENGTH + message.data.len());
+        data.extend_from_slice(MAGIC);
+        data.push(VERSION);
+        data.push(CIPHER_XCHACHA20_POLY1305);
+        match message.kdf {
+            None => data.push(KDF_NONE),
+            Some(Kdf::Pbkdf2 { iterations }) => {
+                data.push(KDF_PBKDF2);
+                data.extend_from_slice(&iterations.to_be_bytes());
+            }
+            Some(Kdf::Argon2id {
+                memory,
+                iterations,
+                parallelism,
+            }) => {
+                data.push(KDF_ARGON2ID);
+                data.extend_from_slice(&memory.to_be_bytes());
+                data.extend_from_slice(&iterations.to_be_bytes());
+                data.extend_from_slice(&parallelism.to_be_bytes());
+            }
+        }
+        data.extend_from_slice(&message.salt);
+        data.extend_from_slice(&message.data);
+
+        data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(kdf: Option<Kdf>) -> CryptedMessage {
+        CryptedMessage {
+            salt: [7; SALT_LENGTH],
+            data: vec![1, 2, 3],
+            kdf,
+        }
+    }
+
+    #[test]
+    fn versioned_layout() {
+        let data: Vec<u8> = message(None).into();
+        assert_eq!(&data[..7], b"MKCM\x01\x01\x00");
+        assert_eq!(&data[7..23], &[7; SALT_LENGTH]);
+        assert_eq!(&data[23..], &[1, 2, 3]);
+
+        let data: Vec<u8> = message(Some(Kdf::Pbkdf2 { iterations: 258 })).into();
+        assert_eq!(&data[..11], b"MKCM\x01\x01\x01\x00\x00\x01\x02");
+    }
+
+    #[test]
+    fn versioned_round_trip() -> crate::Result<()> {
+        for kdf in &[
+            None,
+            Some(Kdf::Pbkdf2 { iterations: 1_000 }),
+            Some(Kdf::Argon2id {
+                memory: 64,
+                iterations: 3,
+                parallelism: 2,
+            }),
+        ] {
+            let data: Vec<u8> = message(*kdf).into();
+            let read = CryptedMessage::try_from(data)?;
+            assert_eq!(read.salt, [7; SALT_LENGTH]);
+            assert_eq!(read.data, [1, 2, 3]);
+            assert_eq!(read.kdf, *kdf);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn read_v0_with_kdf() -> crate::Result<()> {
+        let legacy = bincode::serialize(&message(Some(Kdf::Pbkdf2 { iterations: 10 }))).unwrap();
+
+        let read = CryptedMessage::try_from(legacy)?;
+        assert_eq!(read.data, [1, 2, 3]);
+        assert_eq!(read.kdf, Some(Kdf::Pbkdf2 { iterations: 10 }));
+
+        Ok(())
+    }
+
+    #[test]
+    fn read_v0_starting_with_magic() -> crate::Result<()> {
+        let mut legacy = message(Some(Kdf::Pbkdf2 { iterations: 10 }));
+        legacy.salt[..MAGIC.len()].copy_from_slice(MAGIC);
+        legacy.salt[MAGIC.len()] = 0xff;
+        let legacy = bincode::serialize(&legacy).unwrap();
+        assert!(legacy.starts_with(MAGIC));
+
+        let read = CryptedMessage::try_from(legacy)?;
+        assert_eq!(&read.salt[..5], b"MKCM\xff");
+        assert_eq!(read.data, [1, 2, 3]);
+        assert_eq!(read.kdf, Some(Kdf::Pbkdf2 { iterations: 10 }));
+
+        Ok(())
+    }
+
+    #[test]
+    fn unsupported_fields() {
+        let data: Vec<u8> = message(None).into();
+
+        let mut altered = data.clone();
+        altered[4] = 2;
+        assert_eq!(
+            CryptedMessage::try_from(altered).unwrap_err(),
+            MyKeyringError::UnsupportedCryptedMessageVersion
+        );
+
+        let mut altered = data.clone();
+        altered[5] = 0;
+        assert_eq!(
+            CryptedMessage::try_from(altered).unwrap_err(),
+            MyKeyringError::UnsupportedAlgorithm
+        );
+
+        let mut altered = data.clone();
+        altered[6] = 3;
+        assert_eq!(
+            CryptedMessage::try_from(altered).unwrap_err(),
+            MyKeyringError::UnsupportedAlgorithm
+        );
+
+        // Parameters of the key derivation out of range
+        for kdf in &[
+            Kdf::Pbkdf2 { iterations: 0 },
+            Kdf::Pbkdf2 {
+                iterations: u32::MAX,
+            },
+            Kdf::Argon2id {
+                memory: u32::MAX,
+                iterations: 3,
+                parallelism: 1,
+            },
+            Kdf::Argon2id {
+                memory: 64,
+                iterations: u32::MAX,
+                parallelism: 1,
+            },
+            Kdf::Argon2id {
+                memory: 1024,
+                iterations: 3,
+                parallelism: 64,
+            },
+            // Just above the caps
+            Kdf::Pbkdf2 {
+                iterations: crate::crypt::MAX_PBKDF2_ITERATIONS + 1,
+            },
+            Kdf::Argon2id {
+                memory: crate::crypt::MAX_ARGON2_MEMORY + 1,
+                iterations: 3,
+                parallelism: 1,
+            },
+            Kdf::Argon2id {
+                memory: 1024,
+                iterations: crate::crypt::MAX_ARGON2_ITERATIONS + 1,
+                parallelism: 1,
+            },
+        ] {
+            let forged: Vec<u8> = message(Some(*kdf)).into();
+            assert_eq!(
+                CryptedMessage::try_from(forged).unwrap_err(),
+                MyKeyringError::InvalidKdfParameters
+            );
+            let legacy = bincode::serialize(&message(Some(*kdf))).unwrap();
+            assert_eq!(
+                CryptedMessage::try_from(legacy).unwrap_err(),
+                MyKeyringError::InvalidKdfParameters
+            );
+        }
+
+        assert_eq!(
+            CryptedMessage::try_from(&data[..20]).unwrap_err(),
+            MyKeyringError::InvalidCryptedMessage
+        );
+        assert_eq!(
+            CryptedMessage::try_from(&data[..5]).unwrap_err(),
+            MyKeyringError::InvalidCryptedMessage
+        );
     }
 }
@@ -17,7 +17,7 @@
 //! let text = "Lorem ipsum dolor sit amet, consectetur adipiscing elit. Donec et ultricies augue.";
 //!
 //! // Encrypt data
-//! let encrypted_data = crypt(shared_secret, text.as_bytes(), None)?;
+//! let encrypted_data = crypt(&shared_secret, text.as_bytes(), None, None)?;
 //!
 //! // Serialize the data to send over a network link or store it
 //! let array_data: Vec<u8> = encrypted_data.into();
@@ -26,28 +26,37 @@
 //! let encrypted_data = CryptedMessage::try_from(array_data)?;
 //!
 //! // Decrypt the data, can return an error if shared_secret and/or iterations is invalid
-//! # let shared_secret = secret_1.as_diffie_hellman(&public_key_2).unwrap();
-//! let data = decrypt(shared_secret, encrypted_data, None)?;
-//! let data_text = String::from_utf8(data).expect("valid utf8 string");
+//! // The decrypted data is wiped from memory once dropped
+//! let data = decrypt(&shared_secret, encrypted_data, None, None)?;
+//! let data_text = std::str::from_utf8(&data).expect("valid utf8 string");
 //!
 //! assert_eq!(text, data_text);
 //! # Ok(())
 //! # }
 //! ```
-use std::convert::{Into, TryInto};
+use std::{
+    convert::Into,
+    time::{Duration, Instant},
+};
 
 use chacha20poly1305::{
-    aead::{Aead, NewAead},
+    aead::{Aead, NewAead, Payload},
     Key as KeyPoly, XChaCha20Poly1305, XNonce,
 };
+use hmac::Hmac;
 use rand_core::{OsRng, RngCore};
+use serde::{Deserialize, Serialize};
 use sha2::Sha512;
 use x448::SharedSecret;
 
 pub use self::message::CryptedMessage;
-use crate::MyKeyringError;
+use crate::{
+    secret::{Locked, SecretBytes},
+    MyKeyringError,
+};
 
 mod message;
+pub mod stream;
 
 /// Salt array length
 pub const SALT_LENGTH: usize = 16;
@@ -55,18 +64,38 @@ pub const SALT_LENGTH: usize = 16;
 pub const KEY_LENGTH: usize = 32;
 /// IV array length
 pub const NONCE_LENGTH: usize = 24;
+/// Default number of PBKDF2 iterations
+pub const PBKDF2_ITERATIONS: u32 = 210_000;
+/// Default Argon2id memory size, in KiB
+pub const ARGON2_MEMORY: u32 = 19 * 1024;
+/// Default number of Argon2id iterations
+pub const ARGON2_ITERATIONS: u32 = 2;
+/// Default Argon2id degree of parallelism
+pub const ARGON2_PARALLELISM: u32 = 1;
+/// Maximal number of PBKDF2 iterations, read messages being untrusted
+pub const MAX_PBKDF2_ITERATIONS: u32 = 2_000_000;
+/// Maximal Argon2id memory size, in KiB, 1 GiB
+pub const MAX_ARGON2_MEMORY: u32 = 1024 * 1024;
+/// Maximal number of Argon2id iterations
+pub const MAX_ARGON2_ITERATIONS: u32 = 32;
+/// Maximal Argon2id degree of parallelism
+pub const MAX_ARGON2_PARALLELISM: u32 = 16;
 /// Length of the derived PBKDF2 used
 const DERIVED_LENGTH: usize = KEY_LENGTH + NONCE_LENGTH;
 
 /// Represent a salt for PBKDF2 derivation
 pub type Salt = [u8; SALT_LENGTH];
 /// Key for encryption and AEAD signature
-type Key = [u8; KEY_LENGTH];
-/// Nonce of the AEAD signature and encryption
-type Nonce = [u8; NONCE_LENGTH];
+pub(crate) type Key = [u8; KEY_LENGTH];
 
-/// Encrypt a message, needing a `shared_secret`, the `data` and an optional
-/// context and application specific `context` for deriving the keys.
+/// Encrypt a message, needing a `shared_secret`, the `data`, an optional
+/// application specific `context` for deriving the keys and optional `aad`
+/// associated data.
+///
+/// The associated data is not stored in the message, but authenticated with
+/// it, and must be given again to [`decrypt`]. It binds the message to where it
+/// is used, like the identifier of an entry and the name of the field, so it
+/// cannot be swapped with another message encrypted with the same secret.
 ///
 /// # Examples
 ///
@@ -82,36 +111,103 @@ type Nonce = [u8; NONCE_LENGTH];
 /// use my_keyring_shared::crypt::crypt;
 ///
 /// let encrypted_data = crypt(
-///     shared_secret,
+///     &shared_secret,
 ///     b"Lorem ipsum dolor sit amet, consectetur adipiscing elit. Donec et ultricies augue.",
 ///     None,
+///     Some(b"01EX4Y5V1DBGM2T3Q5JHCZ1B6S password"),
 /// );
 /// ```
 pub fn crypt(
-    shared_secret: SharedSecret,
+    shared_secret: &SharedSecret,
+    data: &[u8],
+    context: Option<&[u8]>,
+    aad: Option<&[u8]>,
+) -> crate::Result<CryptedMessage> {
+    crypt_with_key(shared_secret.as_bytes(), None, data, context, aad)
+}
+
+/// Encrypt a message with a `password`, needing the `data`, the `kdf` used to
+/// stretch the password, defaulting to Argon2id, an optional application
+/// specific `context` for deriving the keys and optional `aad` associated
+/// data, see [`crypt`].
+///
+/// The key derivation function parameters are stored in the message, so
+/// messages encrypted before a change of the default cost stay readable.
+///
+/// # Errors
+///
+/// The value `MyKeyringError::InvalidKdfParameters` is returned if the `kdf`
+/// parameters are out of range.
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> my_keyring_shared::Result<()> {
+/// use my_keyring_shared::crypt::{crypt_with_password, decrypt_with_password, Kdf};
+///
+/// # let kdf = Kdf::Pbkdf2 { iterations: 1_000 };
+/// let encrypted_data =
+///     crypt_with_password(b"My_secr3tP@55w0rd", b"Lorem ipsum", kdf, None, None)?;
+///
+/// let data = decrypt_with_password(b"My_secr3tP@55w0rd", encrypted_data, None, None)?;
+/// assert_eq!(data, b"Lorem ipsum");
+/// # Ok(())
+/// # }
+/// ```
+pub fn crypt_with_password(
+    password: &[u8],
+    data: &[u8],
+    kdf: impl Into<Option<Kdf>>,
+    context: Option<&[u8]>,
+    aad: Option<&[u8]>,
+) -> crate::Result<CryptedMessage> {
+    let kdf = kdf.into().unwrap_or_default();
+
+    // The same salt is used to stretch the password and derive the keys
+    let salt = random_salt();
+    let key = kdf.derive(password, &salt)?;
+    let mut encrypted_message = crypt_with_key(&key[..], Some(salt), data, context, aad)?;
+    encrypted_message.kdf = Some(kdf);
+
+    Ok(encrypted_message)
+}
+
+/// Encrypt a message like [`crypt`], but from any `secret` bytes, not only a
+/// Diffie-Hellman shared secret, and an optional `salt`, randomly generated if
+/// not specified
+pub(crate) fn crypt_with_key(
+    secret: &[u8],
+    salt: Option<Salt>,
     data: &[u8],
     context: Option<&[u8]>,
+    aad: Option<&[u8]>,
 ) -> crate::Result<CryptedMessage> {
-    // Derive the shared_secret
-    let (salt, key, nonce) =
-        derive_keys(shared_secret.as_bytes(), None, context.unwrap_or_default())?;
+    // Derive the secret
+    let (salt, keys) = derive_keys(secret, salt, context.unwrap_or_default())?;
+    let (key, nonce) = &*keys;
 
     // store data
     let mut encrypted_message = CryptedMessage {
         salt,
         data: data[..].to_vec(),
+        kdf: None,
     };
 
-    // Encrypt
-    encrypted_message.data = XChaCha20Poly1305::new(&key)
-        .encrypt(&nonce, data)
+    // Encrypt, and authenticate the associated data
+    let payload = Payload {
+        msg: data,
+        aad: aad.unwrap_or_default(),
+    };
+    encrypted_message.data = XChaCha20Poly1305::new(key)
+        .encrypt(nonce, payload)
         .map_err(|_| MyKeyringError::DataLengthExceeded)?;
 
     Ok(encrypted_message)
 }
 
 /// Decrypt an encrypted message, based on `based_secret`, the `encrypted`
-/// message data and an optional context and application specific `context`.
+/// message data, an optional application specific `context` and the optional
+/// `aad` associated data given to [`crypt`].
 ///
 /// # Examples
 ///
@@ -125,14 +221,20 @@ pub fn crypt(
 /// # let receive_encrypted_message = || {
 /// #     use my_keyring_shared::crypt::crypt;
 /// #     crypt(
-/// #         secret_1.as_diffie_hellman(&public_key_2).unwrap(),
+/// #         &secret_1.as_diffie_hellman(&public_key_2).unwrap(),
 /// #         b"Lorem ipsum dolor sit amet, consectetur adipiscing elit. Donec et ultricies augue.",
 /// #         None,
+/// #         Some(b"01EX4Y5V1DBGM2T3Q5JHCZ1B6S password"),
 /// #     ).unwrap()
 /// # };
 /// use my_keyring_shared::crypt::decrypt;
 ///
-/// let decrypted_data = decrypt(shared_secret, receive_encrypted_message(), None);
+/// let decrypted_data = decrypt(
+///     &shared_secret,
+///     receive_encrypted_message(),
+///     None,
+///     Some(b"01EX4Y5V1DBGM2T3Q5JHCZ1B6S password"),
+/// );
 ///
 /// assert!(decrypted_data.is_ok());
 /// assert_eq!(
@@ -144,40 +246,220 @@ pub fn crypt(
 /// # Errors
 ///
 /// The value `MyKeyringError::IncorrectHmac` can be returned if the
-/// `shared_secret` is not valid, so the HMAC signature cannot be checked, or if
-/// the message has been altered
+/// `shared_secret` is not valid, so the HMAC signature cannot be checked, if
+/// the `aad` differs from the one used to encrypt, or if the message has been
+/// altered
 pub fn decrypt(
-    shared_secret: SharedSecret,
+    shared_secret: &SharedSecret,
     encrypted: CryptedMessage,
     context: Option<&[u8]>,
-) -> crate::Result<Vec<u8>> {
-    // Derive the shared_secret
-    let (_salt, key, nonce) = derive_keys(
-        shared_secret.as_bytes(),
-        Some(encrypted.salt),
-        context.unwrap_or_default(),
-    )?;
-
-    // Verify the Tag message, and so check the Key and Nonce, and decrypt
-    let data = XChaCha20Poly1305::new(&key)
-        .decrypt(&nonce, encrypted.data.as_ref())
+    aad: Option<&[u8]>,
+) -> crate::Result<SecretBytes> {
+    decrypt_with_key(shared_secret.as_bytes(), encrypted, context, aad)
+}
+
+/// Decrypt a message encrypted with [`crypt_with_password`], based on the
+/// `password`, the `encrypted` message data, an optional application specific
+/// `context` and the optional `aad` associated data.
+///
+/// # Errors
+///
+/// The value `MyKeyringError::IncorrectHmac` can be returned if the
+/// `password` or the `aad` is not valid, or if the message has been altered,
+/// and
+/// `MyKeyringError::InvalidCryptedMessage` if the message has not been
+/// encrypted with a password
+pub fn decrypt_with_password(
+    password: &[u8],
+    encrypted: CryptedMessage,
+    context: Option<&[u8]>,
+    aad: Option<&[u8]>,
+) -> crate::Result<SecretBytes> {
+    let kdf = encrypted.kdf.ok_or(MyKeyringError::InvalidCryptedMessage)?;
+    let key = kdf.derive(password, &encrypted.salt)?;
+
+    decrypt_with_key(&key[..], encrypted, context, aad)
+}
+
+/// Decrypt a message encrypted with [`crypt_with_key`]
+pub(crate) fn decrypt_with_key(
+    secret: &[u8],
+    encrypted: CryptedMessage,
+    context: Option<&[u8]>,
+    aad: Option<&[u8]>,
+) -> crate::Result<SecretBytes> {
+    // Derive the secret
+    let (_salt, keys) = derive_keys(secret, Some(encrypted.salt), context.unwrap_or_default())?;
+    let (key, nonce) = &*keys;
+
+    // Verify the Tag message, and so check the Key, Nonce and associated data,
+    // and decrypt
+    let payload = Payload {
+        msg: encrypted.data.as_ref(),
+        aad: aad.unwrap_or_default(),
+    };
+    let data = XChaCha20Poly1305::new(key)
+        .decrypt(nonce, payload)
         .map_err(|_| MyKeyringError::IncorrectHmac)?;
 
-    Ok(data)
+    Ok(data.into())
+}
+
+/// Key derivation function used to stretch a password into a secret
+#[derive(Debug, PartialEq, Copy, Clone, Serialize, Deserialize)]
+pub enum Kdf {
+    /// PBKDF2-HMAC-SHA512 with the number of `iterations`
+    Pbkdf2 {
+        /// Number of iterations, the higher the slower, from 1 to
+        /// [`MAX_PBKDF2_ITERATIONS`]
+        iterations: u32,
+    },
+    /// Argon2id, memory-hard, version 1.3
+    Argon2id {
+        /// Memory size in KiB, at least 8 times the `parallelism`, up to
+        /// [`MAX_ARGON2_MEMORY`]
+        memory: u32,
+        /// Number of passes over the memory, from 1 to
+        /// [`MAX_ARGON2_ITERATIONS`]
+        iterations: u32,
+        /// Degree of parallelism, from 1 to [`MAX_ARGON2_PARALLELISM`]
+        parallelism: u32,
+    },
+}
+
+impl Default for Kdf {
+    fn default() -> Self {
+        Self::Argon2id {
+            memory: ARGON2_MEMORY,
+            iterations: ARGON2_ITERATIONS,
+            parallelism: ARGON2_PARALLELISM,
+        }
+    }
+}
+
+impl Kdf {
+    /// Pick the Argon2id parameters so that a derivation lasts about `target`
+    /// on this device, using `memory` KiB and `parallelism` lanes
+    ///
+    /// The number of iterations is computed from a single pass measure, and is
+    /// at least 1 and at most [`MAX_ARGON2_ITERATIONS`], so the derivation can
+    /// last longer than `target` if the memory size is too high for the
+    /// device.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> my_keyring_shared::Result<()> {
+    /// use std::time::Duration;
+    ///
+    /// use my_keyring_shared::crypt::Kdf;
+    ///
+    /// let kdf = Kdf::calibrate_argon2id(Duration::from_millis(50), 1024, 1)?;
+    /// # println!("{:?}", kdf);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// The value `MyKeyringError::InvalidKdfParameters` is returned if the
+    /// `memory` and `parallelism` parameters are out of range.
+    pub fn calibrate_argon2id(
+        target: Duration,
+        memory: u32,
+        parallelism: u32,
+    ) -> crate::Result<Self> {
+        let single_pass = Self::Argon2id {
+            memory,
+            iterations: 1,
+            parallelism,
+        };
+
+        let start = Instant::now();
+        single_pass.derive(b"calibration", &random_salt())?;
+        let elapsed = start.elapsed().as_nanos().max(1);
+
+        Ok(Self::Argon2id {
+            memory,
+            iterations: (target.as_nanos() / elapsed).clamp(1, u128::from(MAX_ARGON2_ITERATIONS))
+                as u32,
+            parallelism,
+        })
+    }
+
+    /// Check the parameters are in range, before deriving anything with them
+    ///
+    /// The parameters are read from untrusted messages, before their
+    /// authentication, and bounded to what a legitimate message would use: a
+    /// forged one still costs up to 1 GiB and a few seconds to derive, which
+    /// the callers must be ready for, but not an unbounded allocation.
+    pub(crate) fn check(self) -> crate::Result<()> {
+        let valid = match self {
+            Self::Pbkdf2 { iterations } => (1..=MAX_PBKDF2_ITERATIONS).contains(&iterations),
+            Self::Argon2id {
+                memory,
+                iterations,
+                parallelism,
+            } => {
+                (1..=MAX_ARGON2_PARALLELISM).contains(&parallelism)
+                    && (8 * parallelism..=MAX_ARGON2_MEMORY).contains(&memory)
+                    && (1..=MAX_ARGON2_ITERATIONS).contains(&iterations)
+            }
+        };
+
+        if valid {
+            Ok(())
+        } else {
+            Err(MyKeyringError::InvalidKdfParameters)
+        }
+    }
+
+    /// Stretch a `password` into a `KEY_LENGTH` secret, using the `salt`, kept
+    /// out of the swap
+    pub(crate) fn derive(self, password: &[u8], salt: &Salt) -> crate::Result<Locked<Key>> {
+        self.check()?;
+
+        let mut key = Locked::new([0; KEY_LENGTH]);
+        match self {
+            Self::Pbkdf2 { iterations } => {
+                pbkdf2::pbkdf2::<Hmac<Sha512>>(password, salt, iterations, &mut *key)
+            }
+            Self::Argon2id {
+                memory,
+                iterations,
+                parallelism,
+            } => {
+                let params = argon2::Params::new(memory, iterations, parallelism, Some(KEY_LENGTH))
+                    .map_err(|_| MyKeyringError::InvalidKdfParameters)?;
+                argon2::Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params)
+                    .hash_password_into(password, salt, &mut *key)
+                    .map_err(|_| MyKeyringError::InvalidKdfParameters)?;
+            }
+        }
+        Ok(key)
+    }
+}
+
+/// Generate a random salt
+pub(crate) fn random_salt() -> Salt {
+    let mut salt = [0; SALT_LENGTH];
+    OsRng::fill_bytes(&mut OsRng, &mut salt[..]);
+    salt
 }
 
-/// Split the derived keys into usable array data
-fn split_keys(keys: &[u8]) -> crate::Result<(KeyPoly, XNonce)> {
+/// Split the derived keys into usable array data, kept out of the swap
+fn split_keys(keys: &[u8]) -> crate::Result<Locked<(KeyPoly, XNonce)>> {
+    if keys.len() != DERIVED_LENGTH {
+        return Err(MyKeyringError::InvalidKeyLength);
+    }
+
+    let mut split = Locked::new((KeyPoly::default(), XNonce::default()));
     // Extract the Nonce
-    let nonce: Nonce = (&keys[..NONCE_LENGTH])
-        .try_into()
-        .map_err(|_| MyKeyringError::InvalidKeyLength)?;
+    split.1.copy_from_slice(&keys[..NONCE_LENGTH]);
     // extract the first key (encryption)
-    let key: Key = (&keys[NONCE_LENGTH..(NONCE_LENGTH + KEY_LENGTH)])
-        .try_into()
-        .map_err(|_| MyKeyringError::InvalidKeyLength)?;
+    split.0.copy_from_slice(&keys[NONCE_LENGTH..]);
 
-    Ok((key.into(), nonce.into()))
+    Ok(split)
 }
 
 /// Derive a password
@@ -187,35 +469,33 @@ fn split_keys(keys: &[u8]) -> crate::Result<(KeyPoly, XNonce)> {
 /// data, globally unique, and application-specific.
 ///
 /// It returns the `salt`, either randomly generated if not specified or the
-/// `nonce` value if passed, a derived `key` and a `nonce` based on the input
-/// parameters.
+/// `nonce` value if passed, and a derived `key` and a `nonce` based on the
+/// input parameters, both kept out of the swap and wiped once dropped.
 fn derive_keys(
     shared: &[u8],
     nonce: Option<Salt>,
     context: &[u8],
-) -> crate::Result<(Salt, KeyPoly, XNonce)> {
-    let mut hex = [0; DERIVED_LENGTH];
+) -> crate::Result<(Salt, Locked<(KeyPoly, XNonce)>)> {
+    // Kept out of the swap, and wiped once split into the key and the nonce
+    let mut hex = Locked::new([0; DERIVED_LENGTH]);
     // Generate a salt if none
     let salt = match nonce {
         Some(nonce) => nonce,
-        None => {
-            // Compute a random salt
-            let mut nonce = [0; SALT_LENGTH];
-            OsRng::fill_bytes(&mut OsRng, &mut nonce[..]);
-            nonce
-        }
+        // Compute a random salt
+        None => random_salt(),
     };
     hkdf::Hkdf::<Sha512>::new(Some(&salt), shared)
-        .expand(context, &mut hex)
+        .expand(context, &mut *hex)
         .unwrap();
 
-    let (key, nonce) = split_keys(&hex)?;
     // Return the array
-    Ok((salt, key, nonce))
+    Ok((salt, split_keys(&hex[..])?))
 }
 
 #[cfg(test)]
 mod tests {
+    use std::convert::TryFrom;
+
     use test::Bencher;
     use x448::{PublicKey, Secret};
 
@@ -250,7 +530,7 @@ mod tests {
 
             let shared = secret_1.as_diffie_hellman(&public_key_2).unwrap();
 
-            test::black_box(super::crypt(shared, MESSAGE, None).unwrap());
+            test::black_box(super::crypt(&shared, MESSAGE, None, None).unwrap());
         })
     }
 
@@ -265,8 +545,8 @@ mod tests {
         let shared_1 = secret_1.as_diffie_hellman(&public_key_2).unwrap();
         let shared_2 = secret_2.as_diffie_hellman(&public_key_1).unwrap();
 
-        let encrypted = crypt(shared_1, MESSAGE, Some(b"a"))?;
-        let clear = decrypt(shared_2, encrypted, Some(b"a"))?;
+        let encrypted = crypt(&shared_1, MESSAGE, Some(b"a"), None)?;
+        let clear = decrypt(&shared_2, encrypted, Some(b"a"), None)?;
         assert_eq!(MESSAGE, &clear);
 
         Ok(())
@@ -284,9 +564,9 @@ mod tests {
         let shared_1 = secret_1.as_diffie_hellman(&public_key_2).unwrap();
         let shared_2 = secret_3.as_diffie_hellman(&public_key_1).unwrap();
 
-        let encrypted = crypt(shared_1, MESSAGE, None)?;
+        let encrypted = crypt(&shared_1, MESSAGE, None, None)?;
 
-        let wrong = decrypt(shared_2, encrypted, None);
+        let wrong = decrypt(&shared_2, encrypted, None, None);
         assert!(wrong.is_err());
         assert_eq!(wrong.unwrap_err(), MyKeyringError::IncorrectHmac);
 
@@ -304,12 +584,156 @@ mod tests {
         let shared_1 = secret_1.as_diffie_hellman(&public_key_2).unwrap();
         let shared_2 = secret_2.as_diffie_hellman(&public_key_1).unwrap();
 
-        let encrypted = crypt(shared_1, MESSAGE, None)?;
+        let encrypted = crypt(&shared_1, MESSAGE, None, None)?;
 
-        let wrong = decrypt(shared_2, encrypted, Some(b"Other context"));
+        let wrong = decrypt(&shared_2, encrypted, Some(b"Other context"), None);
         assert!(wrong.is_err());
         assert_eq!(wrong.unwrap_err(), MyKeyringError::IncorrectHmac);
 
         Ok(())
     }
+
+    #[test]
+    fn crypt_then_different_aad() -> crate::Result<()> {
+        let secret = Secret::new(&mut OsRng);
+        let aad: &[u8] = b"01EX4Y5V1DBGM2T3Q5JHCZ1B6S password";
+
+        let encrypted = crypt_with_key(secret.as_bytes(), None, MESSAGE, None, Some(aad))?;
+        let salt = encrypted.salt;
+        let data = encrypted.data.clone();
+        assert_eq!(
+            MESSAGE,
+            &decrypt_with_key(secret.as_bytes(), encrypted, None, Some(aad))?[..]
+        );
+
+        // Same message, used for another entry
+        for wrong_aad in &[None, Some(&b"01EX4Y5V1DBGM2T3Q5JHCZ1B6T password"[..])] {
+            let swapped = CryptedMessage {
+                salt,
+                data: data.clone(),
+                kdf: None,
+            };
+            let wrong = decrypt_with_key(secret.as_bytes(), swapped, None, *wrong_aad);
+            assert_eq!(wrong.unwrap_err(), MyKeyringError::IncorrectHmac);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn crypt_with_password_then_decrypt() -> crate::Result<()> {
+        let kdf = Kdf::Pbkdf2 { iterations: 10 };
+        let encrypted = crypt_with_password(b"password", MESSAGE, kdf, Some(b"a"), None)?;
+
+        // The iterations count is kept in the serialized message
+        let encrypted: Vec<u8> = encrypted.into();
+        let encrypted = CryptedMessage::try_from(encrypted)?;
+        assert_eq!(encrypted.kdf, Some(kdf));
+
+        let clear = decrypt_with_password(b"password", encrypted, Some(b"a"), None)?;
+        assert_eq!(MESSAGE, &clear);
+
+        Ok(())
+    }
+
+    #[test]
+    fn crypt_with_password_then_wrong_password() -> crate::Result<()> {
+        let kdf = Kdf::Pbkdf2 { iterations: 10 };
+        let encrypted = crypt_with_password(b"password", MESSAGE, kdf, None, None)?;
+
+        let wrong = decrypt_with_password(b"Password", encrypted, None, None);
+        assert_eq!(wrong.unwrap_err(), MyKeyringError::IncorrectHmac);
+
+        Ok(())
+    }
+
+    #[test]
+    fn decrypt_with_password_needs_kdf() -> crate::Result<()> {
+        let encrypted = crypt_with_key(b"password", None, MESSAGE, None, None)?;
+
+        let wrong = decrypt_with_password(b"password", encrypted, None, None);
+        assert_eq!(wrong.unwrap_err(), MyKeyringError::InvalidCryptedMessage);
+
+        Ok(())
+    }
+
+    #[test]
+    fn read_legacy_message() -> crate::Result<()> {
+        let secret_1 = Secret::new(&mut OsRng);
+        let public_key_1 = PublicKey::from(&secret_1);
+        let secret_2 = Secret::new(&mut OsRng);
+        let public_key_2 = PublicKey::from(&secret_2);
+        let shared_1 = secret_1.as_diffie_hellman(&public_key_2).unwrap();
+        let shared_2 = secret_2.as_diffie_hellman(&public_key_1).unwrap();
+
+        // Serialized without the kdf field
+        let encrypted = crypt(&shared_1, MESSAGE, None, None)?;
+        let legacy = bincode::serialize(&(encrypted.salt, &encrypted.data)).unwrap();
+
+        let encrypted = CryptedMessage::try_from(legacy)?;
+        assert_eq!(encrypted.kdf, None);
+        assert_eq!(MESSAGE, &decrypt(&shared_2, encrypted, None, None)?[..]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn crypt_with_argon2id_then_decrypt() -> crate::Result<()> {
+        let kdf = Kdf::Argon2id {
+            memory: 64,
+            iterations: 1,
+            parallelism: 2,
+        };
+        let encrypted = crypt_with_password(b"password", MESSAGE, kdf, None, None)?;
+
+        let encrypted: Vec<u8> = encrypted.into();
+        let encrypted = CryptedMessage::try_from(encrypted)?;
+        assert_eq!(encrypted.kdf, Some(kdf));
+
+        let clear = decrypt_with_password(b"password", encrypted, None, None)?;
+        assert_eq!(MESSAGE, &clear);
+
+        Ok(())
+    }
+
+    #[test]
+    fn argon2id_invalid_parameters() {
+        for &(memory, iterations, parallelism) in &[(8, 0, 1), (8, 1, 0), (15, 1, 2)] {
+            let kdf = Kdf::Argon2id {
+                memory,
+                iterations,
+                parallelism,
+            };
+            let wrong = crypt_with_password(b"password", MESSAGE, kdf, None, None);
+            assert_eq!(wrong.unwrap_err(), MyKeyringError::InvalidKdfParameters);
+        }
+    }
+
+    #[test]
+    fn argon2id_calibration() -> crate::Result<()> {
+        let kdf = Kdf::calibrate_argon2id(Duration::from_secs(3_600), 64, 1)?;
+        match kdf {
+            Kdf::Argon2id {
+                memory,
+                iterations,
+                parallelism,
+            } => {
+                assert_eq!(memory, 64);
+                assert!(iterations > 1);
+                assert_eq!(parallelism, 1);
+            }
+            _ => panic!("Argon2id expected"),
+        }
+
+        // Never less than one pass
+        let kdf = Kdf::calibrate_argon2id(Duration::from_nanos(1), 64, 1)?;
+        assert!(matches!(kdf, Kdf::Argon2id { iterations: 1, .. }));
+
+        assert_eq!(
+            Kdf::calibrate_argon2id(Duration::from_secs(1), 8, 0).unwrap_err(),
+            MyKeyringError::InvalidKdfParameters
+        );
+
+        Ok(())
+    }
 }
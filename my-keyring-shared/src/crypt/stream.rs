@@ -0,0 +1,344 @@
+//! Chunked encryption of a stream, to encrypt big data without keeping it in
+//! memory
+//!
+//! The construction is STREAM, from "Online Authenticated-Encryption and its
+//! Nonce-Reuse Misuse-Resistance": the data is split into [`CHUNK_LENGTH`]
+//! chunks, each one encrypted with XChaCha20-Poly1305 and a nonce made of the
+//! HKDF derived nonce prefix, the big endian index of the chunk and a flag set
+//! only for the last chunk. Reordered chunks fail the authentication because
+//! of their index, and a truncated stream because its last chunk has not the
+//! flag set.
+//!
+//! # Wire format
+//!
+//! | Field   | Length | Content                                            |
+//! |---------|--------|----------------------------------------------------|
+//! | magic   | 4      | `MKCS`                                             |
+//! | version | 1      | `1`                                                |
+//! | salt    | 16     | salt of the key derivation                         |
+//! | chunks  | ..     | [`CHUNK_LENGTH`] encrypted bytes and 16 bytes tag  |
+//! | last    | 16..   | less than [`CHUNK_LENGTH`] bytes, maybe none       |
+//!
+//! # Examples
+//!
+//! ```
+//! # fn main() -> my_keyring_shared::Result<()> {
+//! # use x448::{Secret, PublicKey};
+//! # use rand_core::OsRng;
+//! # let secret_1 = Secret::new(&mut OsRng);
+//! # let secret_2 = Secret::new(&mut OsRng);
+//! # let public_key_2 = PublicKey::from(&secret_2);
+//! # let shared_secret = secret_1.as_diffie_hellman(&public_key_2).unwrap();
+//! use my_keyring_shared::crypt::stream::{crypt_stream, decrypt_stream};
+//!
+//! let attachment = vec![42; 200_000];
+//!
+//! // Encrypt from any reader, to any writer, like files
+//! let mut encrypted = Vec::new();
+//! crypt_stream(&shared_secret, &attachment[..], &mut encrypted, None, None)?;
+//!
+//! let mut decrypted = Vec::new();
+//! decrypt_stream(&shared_secret, &encrypted[..], &mut decrypted, None, None)?;
+//!
+//! assert_eq!(attachment, decrypted);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::io::{ErrorKind, Read, Write};
+
+use chacha20poly1305::{
+    aead::{Aead, NewAead, Payload},
+    XChaCha20Poly1305, XNonce,
+};
+use x448::SharedSecret;
+
+use crate::{
+    crypt::{derive_keys, Salt, NONCE_LENGTH, SALT_LENGTH},
+    secret::SecretBytes,
+    MyKeyringError,
+};
+
+/// Magic number at the beginning of every encrypted stream
+const MAGIC: &[u8; 4] = b"MKCS";
+/// Current version of the stream format
+const VERSION: u8 = 1;
+/// Length of the clear data of each chunk
+pub const CHUNK_LENGTH: usize = 64 * 1024;
+/// Length of the authentication tag appended to each chunk
+const TAG_LENGTH: usize = 16;
+/// Length of the nonce prefix, the remaining being the index and the last flag
+const PREFIX_LENGTH: usize = NONCE_LENGTH - 5;
+
+/// Encrypt everything from the `reader` into the `writer`, chunk by chunk,
+/// needing a `shared_secret`, an optional application specific `context` for
+/// deriving the keys and optional `aad` associated data, authenticated with
+/// every chunk
+///
+/// # Errors
+///
+/// Return [`MyKeyringError::Io`] if the `reader` or the `writer` fails, and
+/// [`MyKeyringError::DataLengthExceeded`] if there are more than 2^32 chunks
+pub fn crypt_stream(
+    shared_secret: &SharedSecret,
+    reader: impl Read,
+    writer: impl Write,
+    context: Option<&[u8]>,
+    aad: Option<&[u8]>,
+) -> crate::Result<()> {
+    crypt_stream_with_key(shared_secret.as_bytes(), reader, writer, context, aad)
+}
+
+/// Encrypt a stream like [`crypt_stream`], but from any `secret` bytes
+pub(crate) fn crypt_stream_with_key(
+    secret: &[u8],
+    mut reader: impl Read,
+    mut writer: impl Write,
+    context: Option<&[u8]>,
+    aad: Option<&[u8]>,
+) -> crate::Result<()> {
+    let (salt, keys) = derive_keys(secret, None, context.unwrap_or_default())?;
+    let (key, nonce) = &*keys;
+    let cipher = XChaCha20Poly1305::new(key);
+
+    write_all(&mut writer, MAGIC)?;
+    write_all(&mut writer, &[VERSION])?;
+    write_all(&mut writer, &salt)?;
+
+    let mut chunk = vec![0; CHUNK_LENGTH];
+    let mut index = 0_u32;
+    loop {
+        // A partial chunk is the last one, even empty
+        let length = fill(&mut reader, &mut chunk)?;
+        let last = length < CHUNK_LENGTH;
+
+        let payload = Payload {
+            msg: &chunk[..length],
+            aad: aad.unwrap_or_default(),
+        };
+        let encrypted = cipher
+            .encrypt(&chunk_nonce(nonce, index, last), payload)
+            .map_err(|_| MyKeyringError::DataLengthExceeded)?;
+        write_all(&mut writer, &encrypted)?;
+
+        if last {
+            return writer.flush().map_err(|_| MyKeyringError::Io);
+        }
+        index = index
+            .checked_add(1)
+            .ok_or(MyKeyringError::DataLengthExceeded)?;
+    }
+}
+
+/// Decrypt everything from the `reader`, encrypted by [`crypt_stream`], into
+/// the `writer`, needing the `shared_secret`, the optional application specific
+/// `context` and the optional `aad` associated data used to encrypt it
+///
+/// Each chunk is written as soon as it is authenticated, so if an error is
+/// returned, what has been written must be discarded.
+///
+/// # Errors
+///
+/// Return [`MyKeyringError::Io`] if the `reader` or the `writer` fails,
+/// [`MyKeyringError::InvalidCryptedMessage`] if the data is not an encrypted
+/// stream, [`MyKeyringError::UnsupportedCryptedMessageVersion`] if it has been
+/// written by a newer format, and [`MyKeyringError::IncorrectHmac`] if the
+/// `shared_secret` or the `aad` is not valid, or if a chunk has been altered,
+/// reordered or removed
+pub fn decrypt_stream(
+    shared_secret: &SharedSecret,
+    reader: impl Read,
+    writer: impl Write,
+    context: Option<&[u8]>,
+    aad: Option<&[u8]>,
+) -> crate::Result<()> {
+    decrypt_stream_with_key(shared_secret.as_bytes(), reader, writer, context, aad)
+}
+
+/// Decrypt a stream encrypted with [`crypt_stream_with_key`]
+pub(crate) fn decrypt_stream_with_key(
+    secret: &[u8],
+    mut reader: impl Read,
+    mut writer: impl Write,
+    context: Option<&[u8]>,
+    aad: Option<&[u8]>,
+) -> crate::Result<()> {
+    let mut header = [0; MAGIC.len() + 1 + SALT_LENGTH];
+    if fill(&mut reader, &mut header)? < header.len() || &header[..MAGIC.len()] != MAGIC {
+        return Err(MyKeyringError::InvalidCryptedMessage);
+    }
+    if header[MAGIC.len()] != VERSION {
+        return Err(MyKeyringError::UnsupportedCryptedMessageVersion);
+    }
+    let mut salt: Salt = [0; SALT_LENGTH];
+    salt.copy_from_slice(&header[MAGIC.len() + 1..]);
+
+    let (_salt, keys) = derive_keys(secret, Some(salt), context.unwrap_or_default())?;
+    let (key, nonce) = &*keys;
+    let cipher = XChaCha20Poly1305::new(key);
+
+    let mut chunk = vec![0; CHUNK_LENGTH + TAG_LENGTH];
+    let mut index = 0_u32;
+    loop {
+        let length = fill(&mut reader, &mut chunk)?;
+        let last = length < chunk.len();
+
+        let payload = Payload {
+            msg: &chunk[..length],
+            aad: aad.unwrap_or_default(),
+        };
+        let decrypted: SecretBytes = cipher
+            .decrypt(&chunk_nonce(nonce, index, last), payload)
+            .map_err(|_| MyKeyringError::IncorrectHmac)?
+            .into();
+        write_all(&mut writer, &decrypted)?;
+
+        if last {
+            return writer.flush().map_err(|_| MyKeyringError::Io);
+        }
+        index = index.checked_add(1).ok_or(MyKeyringError::IncorrectHmac)?;
+    }
+}
+
+/// Compute the nonce of the chunk `index`, from the derived `nonce` prefix
+fn chunk_nonce(nonce: &XNonce, index: u32, last: bool) -> XNonce {
+    let mut chunk_nonce = *nonce;
+    chunk_nonce[PREFIX_LENGTH..NONCE_LENGTH - 1].copy_from_slice(&index.to_be_bytes());
+    chunk_nonce[NONCE_LENGTH - 1] = u8::from(last);
+    chunk_nonce
+}
+
+/// Read from `reader` until `buffer` is full or the end of the data is
+/// reached, returning the number of bytes read
+fn fill(reader: &mut impl Read, buffer: &mut [u8]) -> crate::Result<usize> {
+    let mut length = 0;
+    while length < buffer.len() {
+        match reader.read(&mut buffer[length..]) {
+            Ok(0) => break,
+            Ok(read) => length += read,
+            Err(e) if e.kind() == ErrorKind::Interrupted => {}
+            Err(_) => return Err(MyKeyringError::Io),
+        }
+    }
+    Ok(length)
+}
+
+/// Write the whole `data` to the `writer`
+fn write_all(writer: &mut impl Write, data: &[u8]) -> crate::Result<()> {
+    writer.write_all(data).map_err(|_| MyKeyringError::Io)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &[u8] = b"shared secret";
+    const HEADER_LENGTH: usize = MAGIC.len() + 1 + SALT_LENGTH;
+    const ENCRYPTED_CHUNK_LENGTH: usize = CHUNK_LENGTH + TAG_LENGTH;
+
+    fn encrypt(data: &[u8]) -> crate::Result<Vec<u8>> {
+        let mut encrypted = Vec::new();
+        crypt_stream_with_key(SECRET, data, &mut encrypted, None, None)?;
+        Ok(encrypted)
+    }
+
+    fn decrypt(encrypted: &[u8]) -> crate::Result<Vec<u8>> {
+        let mut decrypted = Vec::new();
+        decrypt_stream_with_key(SECRET, encrypted, &mut decrypted, None, None)?;
+        Ok(decrypted)
+    }
+
+    #[test]
+    fn crypt_then_decrypt() -> crate::Result<()> {
+        for &length in &[
+            0,
+            1,
+            CHUNK_LENGTH - 1,
+            CHUNK_LENGTH,
+            CHUNK_LENGTH + 1,
+            3 * CHUNK_LENGTH + 42,
+        ] {
+            let data: Vec<u8> = (0..length).map(|i| i as u8).collect();
+
+            let encrypted = encrypt(&data)?;
+            // A final chunk, even empty, is always present
+            let chunks = length / CHUNK_LENGTH + 1;
+            assert_eq!(
+                encrypted.len(),
+                HEADER_LENGTH + length + chunks * TAG_LENGTH
+            );
+            assert_eq!(decrypt(&encrypted)?, data);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn truncated() -> crate::Result<()> {
+        let encrypted = encrypt(&[1; 2 * CHUNK_LENGTH + 10])?;
+
+        // At a chunk boundary, the last chunk flag is missing
+        for &length in &[
+            HEADER_LENGTH,
+            HEADER_LENGTH + ENCRYPTED_CHUNK_LENGTH,
+            HEADER_LENGTH + 2 * ENCRYPTED_CHUNK_LENGTH,
+            encrypted.len() - 1,
+        ] {
+            assert_eq!(
+                decrypt(&encrypted[..length]).unwrap_err(),
+                MyKeyringError::IncorrectHmac
+            );
+        }
+        assert_eq!(
+            decrypt(&encrypted[..HEADER_LENGTH - 1]).unwrap_err(),
+            MyKeyringError::InvalidCryptedMessage
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn reordered() -> crate::Result<()> {
+        let data: Vec<u8> = (0..3 * CHUNK_LENGTH)
+            .map(|i| (i / CHUNK_LENGTH) as u8)
+            .collect();
+        let encrypted = encrypt(&data)?;
+
+        let first = HEADER_LENGTH..HEADER_LENGTH + ENCRYPTED_CHUNK_LENGTH;
+        let second = first.end..first.end + ENCRYPTED_CHUNK_LENGTH;
+        let mut swapped = encrypted[..HEADER_LENGTH].to_vec();
+        swapped.extend_from_slice(&encrypted[second.clone()]);
+        swapped.extend_from_slice(&encrypted[first]);
+        swapped.extend_from_slice(&encrypted[second.end..]);
+
+        assert_eq!(
+            decrypt(&swapped).unwrap_err(),
+            MyKeyringError::IncorrectHmac
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn wrong_aad_or_header() -> crate::Result<()> {
+        let mut encrypted = Vec::new();
+        crypt_stream_with_key(SECRET, &b"attachment"[..], &mut encrypted, None, Some(b"a"))?;
+
+        let mut decrypted = Vec::new();
+        let wrong = decrypt_stream_with_key(SECRET, &encrypted[..], &mut decrypted, None, None);
+        assert_eq!(wrong.unwrap_err(), MyKeyringError::IncorrectHmac);
+
+        let mut altered = encrypted.clone();
+        altered[MAGIC.len()] = VERSION + 1;
+        let wrong = decrypt_stream_with_key(SECRET, &altered[..], &mut decrypted, None, Some(b"a"));
+        assert_eq!(
+            wrong.unwrap_err(),
+            MyKeyringError::UnsupportedCryptedMessageVersion
+        );
+
+        decrypt_stream_with_key(SECRET, &encrypted[..], &mut decrypted, None, Some(b"a"))?;
+        assert_eq!(decrypted, b"attachment");
+
+        Ok(())
+    }
+}
@@ -12,4 +12,53 @@ pub enum MyKeyringError {
     InvalidKeyLength,
     /// The length of the data exceed the max allowed value
     DataLengthExceeded,
+    /// The URI does not use the `otpauth://` scheme
+    InvalidUriScheme,
+    /// The OTP type of the URI is not supported
+    UnsupportedOtpType,
+    /// The URI has no `secret` parameter
+    MissingSecret,
+    /// A parameter of the URI has an invalid value
+    InvalidUriParameter,
+    /// The URI contains an invalid percent-encoded sequence
+    InvalidPercentEncoding,
+    /// The OCRA suite string cannot be parsed
+    InvalidOcraSuite,
+    /// The OCRA challenge does not match the suite format
+    InvalidOcraChallenge,
+    /// An input needed by the OCRA suite is missing or has an invalid length
+    InvalidOcraInput,
+    /// The array of byte is not a vault, or its header is corrupted
+    InvalidVault,
+    /// The vault was written by a newer, unknown, format version
+    UnsupportedVaultVersion,
+    /// The parameters of the key derivation function are out of range
+    InvalidKdfParameters,
+    /// The encrypted message was written by a newer, unknown, format version
+    UnsupportedCryptedMessageVersion,
+    /// The cipher or the key derivation function of the message is unknown
+    UnsupportedAlgorithm,
+    /// Reading or writing the data failed
+    Io,
+    /// The recipient is not a known key, or the data is not encrypted for it
+    UnknownRecipient,
+    /// The signature has not been issued by the expected identity
+    InvalidSignature,
+    /// The key rotation does not replace the currently known key
+    StaleKeyRotation,
+    /// The memory holding the key material cannot be locked, it may be
+    /// swapped to disk
+    MemoryNotLocked,
+    /// The date does not exist
+    InvalidDate,
+    /// The card number has not 12 to 19 digits, or fails the Luhn check
+    InvalidCardNumber,
+    /// The SSH private key is not PEM armoured, or the public key is not an
+    /// `authorized_keys` line
+    InvalidSshKey,
+    /// The SSID or the password length of the Wi-Fi network is invalid
+    InvalidWifiNetwork,
+    /// The options of the password or passphrase generator cannot be
+    /// satisfied
+    InvalidGeneratorOptions,
 }
@@ -0,0 +1,464 @@
+//! Contains the password and passphrase generators
+//!
+//! All the randomness comes from the operating system random number
+//! generator, and every result comes with its entropy, in bits: the base 2
+//! logarithm of the number of results the options can give, all equally
+//! likely.
+//!
+//! # Examples
+//!
+//! ```
+//! # fn main() -> my_keyring_shared::Result<()> {
+//! use my_keyring_shared::generator::{passphrase, password, PassphraseOptions, PasswordOptions};
+//!
+//! let generated = password(&PasswordOptions {
+//!     length: 16,
+//!     symbols: None,
+//!     ..Default::default()
+//! })?;
+//! assert_eq!(generated.value().len(), 16);
+//! assert!(generated.entropy() > 90.0);
+//!
+//! // "Acid-Acorn-Actor7-...", 8 words by default
+//! let generated = passphrase(&PassphraseOptions::default())?;
+//! println!("{}, {:.0} bits", generated.value(), generated.entropy());
+//! # Ok(())
+//! # }
+//! ```
+
+use rand::{seq::SliceRandom, Rng};
+use rand_core::{OsRng, RngCore};
+
+use crate::{secret::SecretString, MyKeyringError};
+
+/// Maximal length of a generated password
+pub const MAX_LENGTH: usize = 128;
+/// Characters looking alike in some fonts, excluded on demand
+pub const AMBIGUOUS: &str = "0Oo1Il|`'\"";
+/// Lowercase letters
+const LOWERCASE: &str = "abcdefghijklmnopqrstuvwxyz";
+/// Uppercase letters
+const UPPERCASE: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+/// Decimal digits
+const DIGITS: &str = "0123456789";
+/// ASCII printable symbols
+const SYMBOLS: &str = "!\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~";
+/// Embedded word list, 256 words, also rendering the key fingerprints
+const WORDS: &str = include_str!("keys/words.txt");
+
+/// A generated password or passphrase
+#[derive(Debug, Clone)]
+pub struct Generated {
+    value: SecretString,
+    entropy: f64,
+}
+
+impl Generated {
+    /// Return the password or passphrase
+    #[inline]
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Return the entropy, in bits
+    #[inline]
+    pub fn entropy(&self) -> f64 {
+        self.entropy
+    }
+
+    /// Return the password or passphrase, to store it
+    #[inline]
+    pub fn into_value(self) -> SecretString {
+        self.value
+    }
+}
+
+/// Options of the [`password`] generator
+///
+/// Each character class is used if it has a minimum count, possibly 0.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PasswordOptions {
+    /// Number of characters, up to [`MAX_LENGTH`]
+    pub length: usize,
+    /// Minimum count of lowercase letters
+    pub lowercase: Option<usize>,
+    /// Minimum count of uppercase letters
+    pub uppercase: Option<usize>,
+    /// Minimum count of digits
+    pub digits: Option<usize>,
+    /// Minimum count of ASCII symbols
+    pub symbols: Option<usize>,
+    /// Exclude the [`AMBIGUOUS`] characters
+    pub exclude_ambiguous: bool,
+}
+
+impl Default for PasswordOptions {
+    /// 20 characters, at least one of each class
+    fn default() -> Self {
+        Self {
+            length: 20,
+            lowercase: Some(1),
+            uppercase: Some(1),
+            digits: Some(1),
+            symbols: Some(1),
+            exclude_ambiguous: false,
+        }
+    }
+}
+
+/// Generate a password of random characters
+///
+/// Every password matching the `options` is equally likely.
+///
+/// # Errors
+///
+/// Return [`MyKeyringError::InvalidGeneratorOptions`] if no character class
+/// is used, if the `length` is 0 or above [`MAX_LENGTH`], or if the minimum
+/// counts exceed the `length`
+pub fn password(options: &PasswordOptions) -> crate::Result<Generated> {
+    let classes: Vec<(Vec<char>, usize)> = [
+        (LOWERCASE, options.lowercase),
+        (UPPERCASE, options.uppercase),
+        (DIGITS, options.digits),
+        (SYMBOLS, options.symbols),
+    ]
+    .iter()
+    .filter_map(|&(class, minimum)| {
+        let class = class
+            .chars()
+            .filter(|&c| !options.exclude_ambiguous || !AMBIGUOUS.contains(c))
+            .collect();
+        Some((class, minimum?))
+    })
+    .collect();
+
+    let length = options.length;
+    if classes.is_empty()
+        || length == 0
+        || length > MAX_LENGTH
+        || classes.iter().map(|(_, minimum)| minimum).sum::<usize>() > length
+    {
+        return Err(MyKeyringError::InvalidGeneratorOptions);
+    }
+
+    // Number of ways to fill the `length` positions with the classes from
+    // `index` on, by count of positions, as exponential generating functions
+    let mut suffixes = vec![vec![0.0; length + 1]; classes.len() + 1];
+    suffixes[classes.len()][0] = 1.0;
+    for (index, (class, minimum)) in classes.iter().enumerate().rev() {
+        let terms = class_terms(class.len(), *minimum, length);
+        for total in 0..=length {
+            suffixes[index][total] = (0..=total)
+                .map(|count| terms[count] * suffixes[index + 1][total - count])
+                .sum();
+        }
+    }
+    let factorial: f64 = (1..=length).map(|n| n as f64).product();
+    let entropy = (factorial * suffixes[0][length]).log2();
+
+    // Draw the count of each class, weighted by the number of passwords
+    // having it, then their positions, then the characters
+    let mut positions = Vec::with_capacity(length);
+    let mut remaining = length;
+    for (index, (class, minimum)) in classes.iter().enumerate() {
+        let terms = class_terms(class.len(), *minimum, length);
+        let weights: Vec<f64> = (0..=remaining)
+            .map(|count| terms[count] * suffixes[index + 1][remaining - count])
+            .collect();
+        let count = weighted_index(&weights);
+        positions.extend(std::iter::repeat_n(class, count));
+        remaining -= count;
+    }
+    positions.shuffle(&mut OsRng);
+
+    let value: String = positions
+        .iter()
+        .map(|class| class[OsRng.gen_range(0, class.len())])
+        .collect();
+
+    Ok(Generated {
+        value: value.into(),
+        entropy,
+    })
+}
+
+/// Options of the [`passphrase`] generator
+#[derive(Debug, Clone, PartialEq)]
+pub struct PassphraseOptions {
+    /// Number of words
+    pub words: usize,
+    /// Inserted between the words
+    pub separator: String,
+    /// Capitalise the first letter of each word
+    pub capitalize: bool,
+    /// Number of random digits appended to a random word
+    pub digits: usize,
+}
+
+impl Default for PassphraseOptions {
+    /// 8 capitalised words separated by dashes, and a digit
+    fn default() -> Self {
+        Self {
+            words: 8,
+            separator: "-".to_owned(),
+            capitalize: true,
+            digits: 1,
+        }
+    }
+}
+
+/// Generate a passphrase of random words from the embedded list of 256
+/// words, 8 bits of entropy per word
+///
+/// A longer list, like the EFF long list, can be used with
+/// [`passphrase_with_list`].
+///
+/// # Errors
+///
+/// Return [`MyKeyringError::InvalidGeneratorOptions`] if the number of words
+/// is 0
+pub fn passphrase(options: &PassphraseOptions) -> crate::Result<Generated> {
+    let list: Vec<&str> = WORDS.lines().collect();
+    passphrase_with_list(options, &list)
+}
+
+/// Generate a passphrase of random words from the `list`
+///
+/// The words of the `list` must be unique, for the entropy to be right.
+///
+/// # Errors
+///
+/// Return [`MyKeyringError::InvalidGeneratorOptions`] if the number of words
+/// is 0, or if the `list` has less than 2 words
+pub fn passphrase_with_list(
+    options: &PassphraseOptions,
+    list: &[&str],
+) -> crate::Result<Generated> {
+    if options.words == 0 || list.len() < 2 {
+        return Err(MyKeyringError::InvalidGeneratorOptions);
+    }
+
+    let mut words: Vec<String> = (0..options.words)
+        .map(|_| {
+            let word = list.choose(&mut OsRng).expect("not empty list");
+            if options.capitalize {
+                capitalize(word)
+            } else {
+                (*word).to_owned()
+            }
+        })
+        .collect();
+    let mut entropy = options.words as f64 * (list.len() as f64).log2();
+
+    if options.digits > 0 {
+        let word = OsRng.gen_range(0, words.len());
+        for _ in 0..options.digits {
+            words[word].push(char::from(b'0' + OsRng.gen_range(0, 10)));
+        }
+        entropy += options.digits as f64 * 10_f64.log2();
+        // Which word holds the digits only adds entropy when the words can be
+        // told apart
+        if !options.separator.is_empty() || options.capitalize {
+            entropy += (words.len() as f64).log2();
+        }
+    }
+
+    let value = words.join(&options.separator);
+    for word in &mut words {
+        zeroize::Zeroize::zeroize(word);
+    }
+
+    Ok(Generated {
+        value: value.into(),
+        entropy,
+    })
+}
+
+/// Return the terms of the exponential generating function of a class of
+/// `size` characters used at least `minimum` times: `size^count / count!`
+fn class_terms(size: usize, minimum: usize, length: usize) -> Vec<f64> {
+    let mut terms = Vec::with_capacity(length + 1);
+    let mut term = 1.0;
+    for count in 0..=length {
+        if count > 0 {
+            term *= size as f64 / count as f64;
+        }
+        terms.push(if count < minimum { 0.0 } else { term });
+    }
+    terms
+}
+
+/// Draw an index with a probability proportional to its weight
+fn weighted_index(weights: &[f64]) -> usize {
+    let total: f64 = weights.iter().sum();
+    // 53 random bits, uniform in [0, 1)
+    let mut target = (OsRng.next_u64() >> 11) as f64 / (1_u64 << 53) as f64 * total;
+    for (index, weight) in weights.iter().enumerate() {
+        if target < *weight {
+            return index;
+        }
+        target -= weight;
+    }
+    // Rounding errors, the last possible index
+    weights
+        .iter()
+        .rposition(|weight| *weight > 0.0)
+        .expect("a possible count")
+}
+
+/// Uppercase the first letter of the `word`
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn password_classes() -> crate::Result<()> {
+        let options = PasswordOptions {
+            length: 12,
+            lowercase: Some(2),
+            uppercase: None,
+            digits: Some(3),
+            symbols: Some(0),
+            exclude_ambiguous: true,
+        };
+        for _ in 0..100 {
+            let generated = password(&options)?;
+            let value = generated.value();
+            assert_eq!(value.chars().count(), 12);
+            assert!(value.chars().filter(char::is_ascii_lowercase).count() >= 2);
+            assert!(value.chars().filter(char::is_ascii_digit).count() >= 3);
+            assert!(!value
+                .chars()
+                .any(|c| c.is_ascii_uppercase() || AMBIGUOUS.contains(c)));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn password_entropy() -> crate::Result<()> {
+        // Without minimum, the length times the bits of the alphabet
+        let generated = password(&PasswordOptions {
+            length: 10,
+            lowercase: Some(0),
+            uppercase: None,
+            digits: Some(0),
+            symbols: None,
+            exclude_ambiguous: false,
+        })?;
+        assert!((generated.entropy() - 10.0 * 36_f64.log2()).abs() < 1e-9);
+
+        // 2 characters, a letter and a digit: 2 * 26 * 10
+        let generated = password(&PasswordOptions {
+            length: 2,
+            lowercase: Some(1),
+            uppercase: None,
+            digits: Some(1),
+            symbols: None,
+            exclude_ambiguous: false,
+        })?;
+        assert!((generated.entropy() - 520_f64.log2()).abs() < 1e-9);
+
+        // Only digits, even if the letters are allowed
+        let generated = password(&PasswordOptions {
+            length: 4,
+            lowercase: Some(0),
+            uppercase: None,
+            digits: Some(4),
+            symbols: None,
+            exclude_ambiguous: false,
+        })?;
+        assert!(generated.value().chars().all(|c| c.is_ascii_digit()));
+        assert!((generated.entropy() - 4.0 * 10_f64.log2()).abs() < 1e-9);
+
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_password_options() {
+        for options in [
+            PasswordOptions {
+                length: 3,
+                ..Default::default()
+            },
+            PasswordOptions {
+                length: MAX_LENGTH + 1,
+                ..Default::default()
+            },
+            PasswordOptions {
+                lowercase: None,
+                uppercase: None,
+                digits: None,
+                symbols: None,
+                ..Default::default()
+            },
+        ] {
+            assert_eq!(
+                password(&options).unwrap_err(),
+                MyKeyringError::InvalidGeneratorOptions
+            );
+        }
+    }
+
+    #[test]
+    fn passphrase_options() -> crate::Result<()> {
+        let generated = passphrase(&PassphraseOptions::default())?;
+        let words: Vec<&str> = generated.value().split('-').collect();
+        assert_eq!(words.len(), 8);
+        assert!(words
+            .iter()
+            .all(|word| word.starts_with(|c: char| c.is_ascii_uppercase())));
+        assert_eq!(
+            generated
+                .value()
+                .chars()
+                .filter(char::is_ascii_digit)
+                .count(),
+            1
+        );
+        assert!((generated.entropy() - (64.0 + 10_f64.log2() + 3.0)).abs() < 1e-9);
+
+        let list = ["correct", "horse", "battery", "staple"];
+        let generated = passphrase_with_list(
+            &PassphraseOptions {
+                words: 4,
+                separator: " ".to_owned(),
+                capitalize: false,
+                digits: 0,
+            },
+            &list,
+        )?;
+        assert!(generated
+            .value()
+            .split(' ')
+            .all(|word| list.contains(&word)));
+        assert!((generated.entropy() - 8.0).abs() < 1e-9);
+
+        // The position of the digits is not counted once the words are merged
+        let generated = passphrase_with_list(
+            &PassphraseOptions {
+                words: 4,
+                separator: String::new(),
+                capitalize: false,
+                digits: 2,
+            },
+            &list,
+        )?;
+        assert!((generated.entropy() - (8.0 + 2.0 * 10_f64.log2())).abs() < 1e-9);
+
+        assert_eq!(
+            passphrase_with_list(&PassphraseOptions::default(), &["alone"]).unwrap_err(),
+            MyKeyringError::InvalidGeneratorOptions
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,229 @@
+//! Contains the code to generate HOTP code, as defined in RFC 4226
+//!
+//! Unlike [`Totp`](crate::totp::Totp), the moving factor is a counter that
+//! must be persisted between two generations and incremented each time a code
+//! is consumed.
+//!
+//! # Examples
+//!
+//! ```
+//! # fn main() -> my_keyring_shared::Result<()> {
+//! use my_keyring_shared::hotp::Hotp;
+//! let mut hotp = Hotp::new("GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ", None, None, None)?;
+//!
+//! assert_eq!("755224", hotp.generate());
+//! hotp.increment();
+//! assert_eq!("287082", hotp.generate());
+//! # Ok(())
+//! # }
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    algo::Algorithm,
+    secret::{SecretBytes, SecretString},
+    totp::{constant_time_eq, decimal_code, decode_base32, dynamic_truncation, MAX_DIGITS},
+};
+
+/// Generate a Hotp, based on a counter stored alongside the secret
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> my_keyring_shared::Result<()> {
+/// use my_keyring_shared::{hotp::Hotp, Algorithm};
+/// let hotp = Hotp::new("JBSWY3DPEB3W64TMMQQQ", 6, 42, Algorithm::Sha1)?;
+///
+/// // Current counter
+/// println!("{}", hotp.generate());
+/// // Arbitrary counter, 1234567890 here
+/// println!("{}", hotp.hotp_from_counter(1_234_567_890));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Hotp {
+    /// Secret to use
+    secret: SecretString,
+    /// Number of digits, 6 (default) or 8
+    digits: u8,
+    /// Moving factor, the next counter value to use
+    counter: u64,
+    /// Algorithm to use during Hotp generation
+    algorithm: Algorithm,
+}
+
+impl Hotp {
+    /// Initialise a new Hotp
+    ///
+    /// # Defaults values
+    ///
+    /// The `secret` is indicated from the website,
+    /// `digits` is the desired length, defaulting to 6, between 1 and 10,
+    /// `counter` is the initial counter value, defaulting to 0,
+    /// `algorithm` is the algorithm used to generate, defaulting to Sha-1.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use my_keyring_shared::{hotp::Hotp, Algorithm};
+    /// # fn main() -> my_keyring_shared::Result<()> {
+    /// // Specifying only the secret
+    /// let hotp = Hotp::new("JBSWY3DPEB3W64TMMQQQ", None, None, None)?;
+    ///
+    /// // Specifying the other parameters
+    /// let hotp = Hotp::new("JBSWY3DPEB3W64TMMQQQ", 8, Some(12), Some(Algorithm::Sha1))?;
+    ///
+    /// # println!("{}", hotp.generate());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Return [`MyKeyringError::InvalidBase32`](crate::MyKeyringError::InvalidBase32)
+    /// error if the provided `secret` is not a valid base32 encoded string
+    pub fn new(
+        secret: &str,
+        digits: impl Into<Option<u8>>,
+        counter: impl Into<Option<u64>>,
+        algorithm: impl Into<Option<Algorithm>>,
+    ) -> crate::Result<Self> {
+        // Do this to check base32 string
+        let _ = decode_base32(secret)?;
+
+        Ok(Self {
+            secret: secret.into(),
+            digits: digits.into().unwrap_or(6).clamp(1, MAX_DIGITS),
+            counter: counter.into().unwrap_or_default(),
+            algorithm: algorithm.into().unwrap_or(Algorithm::Sha1),
+        })
+    }
+
+    /// Return the current counter value, the one that must be persisted
+    #[inline]
+    pub fn counter(&self) -> u64 {
+        self.counter
+    }
+
+    /// Retrieve the Hotp value based on the current counter
+    ///
+    /// The counter is not modified, call [`Hotp::increment`] once the code has
+    /// been consumed.
+    #[inline]
+    pub fn generate(&self) -> String {
+        self.hotp_from_counter(self.counter)
+    }
+
+    /// Move the counter to its next value, and return it
+    #[inline]
+    pub fn increment(&mut self) -> u64 {
+        self.counter = self.counter.wrapping_add(1);
+        self.counter
+    }
+
+    /// Retrieve the Hotp, based on the `counter` parameter value
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> my_keyring_shared::Result<()> {
+    /// use my_keyring_shared::hotp::Hotp;
+    /// let hotp = Hotp::new("GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ", None, None, None)?;
+    ///
+    /// assert_eq!("520489", hotp.hotp_from_counter(9));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn hotp_from_counter(&self, counter: u64) -> String {
+        // Compute the Hmac
+        let digest = self.algorithm.hmac(
+            &SecretBytes::from(decode_base32(&self.secret).expect("Base32 decoded string")),
+            &counter.to_be_bytes(),
+        );
+
+        // Truncate
+        decimal_code(dynamic_truncation(&digest), self.digits)
+    }
+
+    /// Check the `code` against the current counter and the `look_ahead` next
+    /// ones, as described in RFC 4226 section 7.4
+    ///
+    /// When the code matches, the counter is resynchronised just after the
+    /// matching value, and this value is returned. Otherwise the counter is
+    /// left untouched.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> my_keyring_shared::Result<()> {
+    /// use my_keyring_shared::hotp::Hotp;
+    /// let mut hotp = Hotp::new("GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ", None, None, None)?;
+    ///
+    /// // The token has been pressed 3 times without being used
+    /// assert_eq!(Some(3), hotp.resync("969429", 5));
+    /// assert_eq!(4, hotp.counter());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn resync(&mut self, code: &str, look_ahead: u64) -> Option<u64> {
+        let last = self.counter.saturating_add(look_ahead);
+        let matched = (self.counter..=last)
+            .find(|&counter| constant_time_eq(&self.hotp_from_counter(counter), code))?;
+
+        self.counter = matched.wrapping_add(1);
+        Some(matched)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 12345678901234567890
+    const SEED: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+    #[test]
+    fn tests_vectors_rfc4226() -> crate::Result<()> {
+        let expected = [
+            "755224", "287082", "359152", "969429", "338314", "254676", "287922", "162583",
+            "399871", "520489",
+        ];
+
+        let mut h = Hotp::new(SEED, None, None, None)?;
+        for code in &expected {
+            assert_eq!(&h.generate(), code);
+            h.increment();
+        }
+        assert_eq!(h.counter(), 10);
+
+        Ok(())
+    }
+
+    #[test]
+    fn resync_in_window() -> crate::Result<()> {
+        let mut h = Hotp::new(SEED, None, 2, None)?;
+
+        // Code of counter 8, at the end of the window
+        assert_eq!(h.resync("399871", 6), Some(8));
+        assert_eq!(h.counter(), 9);
+        // Already consumed
+        assert_eq!(h.resync("399871", 6), None);
+        assert_eq!(h.counter(), 9);
+
+        Ok(())
+    }
+
+    #[test]
+    fn resync_out_of_window() -> crate::Result<()> {
+        let mut h = Hotp::new(SEED, None, None, None)?;
+
+        assert_eq!(h.resync("520489", 5), None);
+        assert_eq!(h.counter(), 0);
+        assert_eq!(h.resync("520489", 9), Some(9));
+        assert_eq!(h.counter(), 10);
+
+        Ok(())
+    }
+}
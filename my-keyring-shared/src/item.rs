@@ -0,0 +1,73 @@
+//! Contains the typed items a vault can hold beside the `Authentication` and
+//! the `Note`
+//!
+//! Every item has an identifier, giving its creation time, and implements
+//! [`Tags`](crate::tag::Tags).
+//!
+//! # Examples
+//!
+//! ```
+//! # fn main() -> my_keyring_shared::Result<()> {
+//! use my_keyring_shared::{item::CreditCard, tag::Tags};
+//! use ulid::Ulid;
+//!
+//! let mut card = CreditCard::new("John Doe", "4111 1111 1111 1111", 2030, 12)?;
+//! card.add_tag(Ulid::new());
+//!
+//! assert_eq!(card.masked_number(), "•••• 1111");
+//! assert!(!card.is_expired());
+//! # Ok(())
+//! # }
+//! ```
+
+pub use self::{
+    api_token::ApiToken,
+    card::{luhn_check, CreditCard, Expiry},
+    identity::Identity,
+    licence::Licence,
+    ssh_key::SshKey,
+    wifi::{WifiNetwork, WifiSecurity},
+};
+
+mod api_token;
+mod card;
+mod identity;
+mod licence;
+mod ssh_key;
+mod wifi;
+
+/// Implement [`Tags`](crate::tag::Tags) and the identifier accessors of an
+/// item, holding `id` and `tags` fields
+macro_rules! impl_item {
+    ($item:ty) => {
+        impl $item {
+            /// Return the identifier
+            #[inline]
+            pub fn id(&self) -> ::ulid::Ulid {
+                self.id
+            }
+
+            /// Return the creation time, taken from the identifier
+            #[inline]
+            pub fn created(&self) -> ::std::time::SystemTime {
+                crate::authentication::ulid_time(self.id)
+            }
+
+            /// Return the identifiers of the tags, see
+            /// [`crate::tag::TagPool`]
+            #[inline]
+            pub fn tag_ids(&self) -> &[::ulid::Ulid] {
+                &self.tags
+            }
+        }
+
+        impl crate::tag::Tags for $item {
+            #[inline]
+            fn tags(&mut self) -> &mut Vec<::ulid::Ulid> {
+                &mut self.tags
+            }
+        }
+    };
+}
+
+use impl_item;
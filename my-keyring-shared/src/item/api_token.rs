@@ -0,0 +1,71 @@
+use serde::{Deserialize, Serialize};
+use ulid::Ulid;
+
+use crate::{authentication::Date, item::impl_item, secret::SecretString};
+
+/// Token giving access to an API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct ApiToken {
+    id: Ulid,
+    tags: Vec<Ulid>,
+    /// Service the token is for
+    name: String,
+    token: SecretString,
+    /// Last day of validity, if the token expires
+    expires: Option<Date>,
+}
+
+impl ApiToken {
+    /// Create the `token` of the service `name`
+    pub fn new(name: &str, token: &str) -> Self {
+        Self {
+            id: Ulid::new(),
+            tags: Vec::new(),
+            name: name.to_owned(),
+            token: token.into(),
+            expires: None,
+        }
+    }
+
+    /// Return the service the token is for
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Rename the service the token is for
+    pub fn set_name(&mut self, name: &str) {
+        self.name = name.to_owned();
+    }
+
+    /// Return the token
+    #[inline]
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    /// Replace the token, after a renewal
+    pub fn set_token(&mut self, token: &str) {
+        self.token = token.into();
+    }
+
+    /// Return the last day of validity
+    #[inline]
+    pub fn expires(&self) -> Option<Date> {
+        self.expires
+    }
+
+    /// Change the last day of validity, none if the token never expires
+    #[inline]
+    pub fn set_expires(&mut self, expires: Option<Date>) {
+        self.expires = expires;
+    }
+
+    /// Return if the token is expired today
+    pub fn is_expired(&self) -> bool {
+        self.expires.is_some_and(|expires| Date::today() > expires)
+    }
+}
+
+impl_item!(ApiToken);
@@ -0,0 +1,343 @@
+use core::convert::TryFrom;
+
+use serde::{Deserialize, Serialize};
+use ulid::Ulid;
+
+use crate::{
+    authentication::{days_in_month, Date},
+    item::impl_item,
+    secret::SecretString,
+    MyKeyringError,
+};
+
+/// Minimal number of digits of a card number, ISO/IEC 7812
+const MIN_DIGITS: usize = 12;
+/// Maximal number of digits of a card number, ISO/IEC 7812
+const MAX_DIGITS: usize = 19;
+
+/// Check the Luhn checksum of a number, given as its ASCII digits
+///
+/// # Examples
+///
+/// ```
+/// use my_keyring_shared::item::luhn_check;
+///
+/// assert!(luhn_check("79927398713"));
+/// assert!(!luhn_check("79927398710"));
+/// ```
+pub fn luhn_check(digits: &str) -> bool {
+    if digits.is_empty() || !digits.bytes().all(|c| c.is_ascii_digit()) {
+        return false;
+    }
+
+    let sum: u32 = digits
+        .bytes()
+        .rev()
+        .enumerate()
+        .map(|(position, c)| {
+            let digit = u32::from(c - b'0');
+            match position % 2 {
+                0 => digit,
+                _ if digit > 4 => digit * 2 - 9,
+                _ => digit * 2,
+            }
+        })
+        .sum();
+
+    sum.is_multiple_of(10)
+}
+
+/// Month of expiry of a card, valid until its last day
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", try_from = "WireExpiry")]
+pub struct Expiry {
+    year: u16,
+    month: u8,
+}
+
+/// Serialised layout of an [`Expiry`], checked by [`Expiry::new`] once read
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+struct WireExpiry {
+    year: u16,
+    month: u8,
+}
+
+impl TryFrom<WireExpiry> for Expiry {
+    type Error = &'static str;
+
+    fn try_from(expiry: WireExpiry) -> Result<Self, Self::Error> {
+        Self::new(expiry.year, expiry.month).map_err(|_| "invalid expiry")
+    }
+}
+
+impl Expiry {
+    /// Create the expiry `month` of the `year`, like `12/30` for 2030
+    ///
+    /// # Errors
+    ///
+    /// Return [`MyKeyringError::InvalidDate`] if the `month` is not between 1
+    /// and 12, or if the `year` has more than 4 digits
+    pub fn new(year: u16, month: u8) -> crate::Result<Self> {
+        Date::new(year, month, 1)?;
+
+        Ok(Self { year, month })
+    }
+
+    /// Return the year
+    #[inline]
+    pub fn year(&self) -> u16 {
+        self.year
+    }
+
+    /// Return the month, from 1 to 12
+    #[inline]
+    pub fn month(&self) -> u8 {
+        self.month
+    }
+
+    /// Return the last day of validity
+    pub fn last_day(&self) -> Date {
+        Date::new(self.year, self.month, days_in_month(self.year, self.month))
+            .expect("valid expiry")
+    }
+
+    /// Return if the card is expired at the `date`
+    #[inline]
+    pub fn is_expired_at(&self, date: Date) -> bool {
+        date > self.last_day()
+    }
+}
+
+/// Payment card
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", try_from = "WireCreditCard")]
+pub struct CreditCard {
+    id: Ulid,
+    tags: Vec<Ulid>,
+    /// Name printed on the card
+    holder: String,
+    /// Digits only, without separators
+    number: SecretString,
+    expiry: Expiry,
+    /// Code printed on the back
+    verification_code: Option<SecretString>,
+    pin: Option<SecretString>,
+}
+
+/// Serialised layout of a [`CreditCard`], its number checked once read
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+struct WireCreditCard {
+    id: Ulid,
+    tags: Vec<Ulid>,
+    holder: String,
+    number: SecretString,
+    expiry: Expiry,
+    verification_code: Option<SecretString>,
+    pin: Option<SecretString>,
+}
+
+impl TryFrom<WireCreditCard> for CreditCard {
+    type Error = &'static str;
+
+    fn try_from(card: WireCreditCard) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: card.id,
+            tags: card.tags,
+            holder: card.holder,
+            number: parse_number(&card.number).map_err(|_| "invalid card number")?,
+            expiry: card.expiry,
+            verification_code: card.verification_code,
+            pin: card.pin,
+        })
+    }
+}
+
+impl CreditCard {
+    /// Create the card `number` of the `holder`, expiring the
+    /// `expiry_month` of the `expiry_year`
+    ///
+    /// The spaces and dashes of the `number` are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Return [`MyKeyringError::InvalidCardNumber`] if the `number` has not 12
+    /// to 19 digits or fails the Luhn check, and
+    /// [`MyKeyringError::InvalidDate`] if the expiry month is invalid
+    pub fn new(
+        holder: &str,
+        number: &str,
+        expiry_year: u16,
+        expiry_month: u8,
+    ) -> crate::Result<Self> {
+        Ok(Self {
+            id: Ulid::new(),
+            tags: Vec::new(),
+            holder: holder.to_owned(),
+            number: parse_number(number)?,
+            expiry: Expiry::new(expiry_year, expiry_month)?,
+            verification_code: None,
+            pin: None,
+        })
+    }
+
+    /// Return the name printed on the card
+    #[inline]
+    pub fn holder(&self) -> &str {
+        &self.holder
+    }
+
+    /// Return the card number, digits only
+    #[inline]
+    pub fn number(&self) -> &str {
+        &self.number
+    }
+
+    /// Return the card number masked but its last 4 digits, to be displayed
+    pub fn masked_number(&self) -> String {
+        let last = self
+            .number
+            .get(self.number.len().saturating_sub(4)..)
+            .unwrap_or_default();
+        format!("•••• {}", last)
+    }
+
+    /// Change the card number, after a renewal
+    ///
+    /// # Errors
+    ///
+    /// Return [`MyKeyringError::InvalidCardNumber`] if the `number` is not
+    /// valid, see [`CreditCard::new`]
+    pub fn set_number(&mut self, number: &str) -> crate::Result<()> {
+        self.number = parse_number(number)?;
+        Ok(())
+    }
+
+    /// Return the month of expiry
+    #[inline]
+    pub fn expiry(&self) -> Expiry {
+        self.expiry
+    }
+
+    /// Change the month of expiry, after a renewal
+    #[inline]
+    pub fn set_expiry(&mut self, expiry: Expiry) {
+        self.expiry = expiry;
+    }
+
+    /// Return if the card is expired today
+    pub fn is_expired(&self) -> bool {
+        self.expiry.is_expired_at(Date::today())
+    }
+
+    /// Return the code printed on the back of the card
+    #[inline]
+    pub fn verification_code(&self) -> Option<&str> {
+        self.verification_code.as_deref()
+    }
+
+    /// Change the code printed on the back of the card
+    pub fn set_verification_code(&mut self, code: Option<&str>) {
+        self.verification_code = code.map(SecretString::from);
+    }
+
+    /// Return the PIN
+    #[inline]
+    pub fn pin(&self) -> Option<&str> {
+        self.pin.as_deref()
+    }
+
+    /// Change the PIN
+    pub fn set_pin(&mut self, pin: Option<&str>) {
+        self.pin = pin.map(SecretString::from);
+    }
+}
+
+impl_item!(CreditCard);
+
+/// Remove the separators of a card `number`, and check it
+fn parse_number(number: &str) -> crate::Result<SecretString> {
+    let digits: SecretString = number
+        .chars()
+        .filter(|&c| c != ' ' && c != '-')
+        .collect::<String>()
+        .into();
+    if !(MIN_DIGITS..=MAX_DIGITS).contains(&digits.len()) || !luhn_check(&digits) {
+        return Err(MyKeyringError::InvalidCardNumber);
+    }
+
+    Ok(digits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn luhn() {
+        for valid in [
+            "4111111111111111",
+            "5555555555554444",
+            "378282246310005",
+            "0",
+        ] {
+            assert!(luhn_check(valid), "{}", valid);
+        }
+        for invalid in ["4111111111111112", "", "4111 1111 1111 1111", "abc"] {
+            assert!(!luhn_check(invalid), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn card_number() -> crate::Result<()> {
+        let mut card = CreditCard::new("John Doe", "4111-1111-1111-1111", 2030, 12)?;
+        assert_eq!(card.number(), "4111111111111111");
+        assert_eq!(card.masked_number(), "•••• 1111");
+
+        for invalid in [
+            "4111 1111 1111 1112",
+            "4111 1111 1",
+            "0000 0000 0000 0000 0000",
+        ] {
+            assert_eq!(
+                card.set_number(invalid).unwrap_err(),
+                MyKeyringError::InvalidCardNumber
+            );
+        }
+        assert_eq!(
+            CreditCard::new("John Doe", "4111111111111111", 2030, 13).unwrap_err(),
+            MyKeyringError::InvalidDate
+        );
+
+        // Checked when read too
+        let read: CreditCard = bincode::deserialize(&bincode::serialize(&card).unwrap()).unwrap();
+        assert_eq!(read.number(), card.number());
+        card.number = "12".into();
+        assert_eq!(card.masked_number(), "•••• 12");
+        assert!(bincode::deserialize::<CreditCard>(&bincode::serialize(&card).unwrap()).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn expiry() -> crate::Result<()> {
+        let expiry = Expiry::new(2024, 2)?;
+        assert_eq!(expiry.last_day(), Date::new(2024, 2, 29)?);
+        assert!(!expiry.is_expired_at(Date::new(2024, 2, 29)?));
+        assert!(expiry.is_expired_at(Date::new(2024, 3, 1)?));
+
+        // Checked when read too
+        let read = |year: u16, month: u8| -> Option<Expiry> {
+            bincode::deserialize(&bincode::serialize(&(year, month)).unwrap()).ok()
+        };
+        assert_eq!(read(2024, 2), Some(expiry));
+        assert_eq!(read(2024, 0), None);
+        assert_eq!(read(2024, 13), None);
+
+        let card = CreditCard::new("John Doe", "4111111111111111", 2020, 1)?;
+        assert!(card.is_expired());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,114 @@
+use serde::{Deserialize, Serialize};
+use ulid::Ulid;
+
+use crate::{authentication::Date, item::impl_item, secret::SecretString};
+
+/// Personal information, to fill forms
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct Identity {
+    id: Ulid,
+    tags: Vec<Ulid>,
+    first_name: String,
+    last_name: String,
+    birth_date: Option<Date>,
+    email: String,
+    phone: String,
+    /// Postal address, on several lines
+    address: String,
+    /// Number of the identity card, the passport or the like
+    document_number: Option<SecretString>,
+}
+
+impl Identity {
+    /// Create the identity of the person `first_name` `last_name`
+    pub fn new(first_name: &str, last_name: &str) -> Self {
+        Self {
+            id: Ulid::new(),
+            tags: Vec::new(),
+            first_name: first_name.to_owned(),
+            last_name: last_name.to_owned(),
+            birth_date: None,
+            email: String::new(),
+            phone: String::new(),
+            address: String::new(),
+            document_number: None,
+        }
+    }
+
+    /// Return the first name
+    #[inline]
+    pub fn first_name(&self) -> &str {
+        &self.first_name
+    }
+
+    /// Return the last name
+    #[inline]
+    pub fn last_name(&self) -> &str {
+        &self.last_name
+    }
+
+    /// Change the first and the last name
+    pub fn set_name(&mut self, first_name: &str, last_name: &str) {
+        self.first_name = first_name.to_owned();
+        self.last_name = last_name.to_owned();
+    }
+
+    /// Return the birth date
+    #[inline]
+    pub fn birth_date(&self) -> Option<Date> {
+        self.birth_date
+    }
+
+    /// Change the birth date
+    #[inline]
+    pub fn set_birth_date(&mut self, birth_date: Option<Date>) {
+        self.birth_date = birth_date;
+    }
+
+    /// Return the email address
+    #[inline]
+    pub fn email(&self) -> &str {
+        &self.email
+    }
+
+    /// Change the email address
+    pub fn set_email(&mut self, email: &str) {
+        self.email = email.to_owned();
+    }
+
+    /// Return the phone number
+    #[inline]
+    pub fn phone(&self) -> &str {
+        &self.phone
+    }
+
+    /// Change the phone number
+    pub fn set_phone(&mut self, phone: &str) {
+        self.phone = phone.to_owned();
+    }
+
+    /// Return the postal address
+    #[inline]
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    /// Change the postal address
+    pub fn set_address(&mut self, address: &str) {
+        self.address = address.to_owned();
+    }
+
+    /// Return the number of the identity document
+    #[inline]
+    pub fn document_number(&self) -> Option<&str> {
+        self.document_number.as_deref()
+    }
+
+    /// Change the number of the identity document
+    pub fn set_document_number(&mut self, document_number: Option<&str>) {
+        self.document_number = document_number.map(SecretString::from);
+    }
+}
+
+impl_item!(Identity);
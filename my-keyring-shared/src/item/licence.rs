@@ -0,0 +1,70 @@
+use serde::{Deserialize, Serialize};
+use ulid::Ulid;
+
+use crate::{authentication::Date, item::impl_item, secret::SecretString};
+
+/// Software licence
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct Licence {
+    id: Ulid,
+    tags: Vec<Ulid>,
+    product: String,
+    /// Person or organisation the licence is registered to
+    licensee: String,
+    key: SecretString,
+    /// Last day of validity, if the licence expires
+    expires: Option<Date>,
+}
+
+impl Licence {
+    /// Create the licence `key` of the `product`, registered to the
+    /// `licensee`
+    pub fn new(product: &str, licensee: &str, key: &str) -> Self {
+        Self {
+            id: Ulid::new(),
+            tags: Vec::new(),
+            product: product.to_owned(),
+            licensee: licensee.to_owned(),
+            key: key.into(),
+            expires: None,
+        }
+    }
+
+    /// Return the product
+    #[inline]
+    pub fn product(&self) -> &str {
+        &self.product
+    }
+
+    /// Return the person or organisation the licence is registered to
+    #[inline]
+    pub fn licensee(&self) -> &str {
+        &self.licensee
+    }
+
+    /// Return the licence key
+    #[inline]
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Return the last day of validity
+    #[inline]
+    pub fn expires(&self) -> Option<Date> {
+        self.expires
+    }
+
+    /// Change the last day of validity, none if the licence is perpetual
+    #[inline]
+    pub fn set_expires(&mut self, expires: Option<Date>) {
+        self.expires = expires;
+    }
+
+    /// Return if the licence is expired today
+    pub fn is_expired(&self) -> bool {
+        self.expires.is_some_and(|expires| Date::today() > expires)
+    }
+}
+
+impl_item!(Licence);
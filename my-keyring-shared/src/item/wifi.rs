@@ -0,0 +1,208 @@
+use core::convert::TryFrom;
+
+use serde::{Deserialize, Serialize};
+use ulid::Ulid;
+
+use crate::{item::impl_item, secret::SecretString, MyKeyringError};
+
+/// Maximal length of a SSID, in bytes
+const MAX_SSID_LENGTH: usize = 32;
+
+/// Security protocol of a Wi-Fi network
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum WifiSecurity {
+    /// No password
+    Open,
+    /// Legacy WEP, with a 5 or 13 characters password, or 10 or 26 hex digits
+    Wep,
+    /// WPA2 or WPA3 personal, with a 8 to 63 characters passphrase, or 64 hex
+    /// digits
+    Wpa,
+}
+
+/// Wi-Fi network
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", try_from = "WireWifiNetwork")]
+pub struct WifiNetwork {
+    id: Ulid,
+    tags: Vec<Ulid>,
+    ssid: String,
+    security: WifiSecurity,
+    /// Empty for the open networks
+    password: SecretString,
+    hidden: bool,
+}
+
+/// Serialised layout of a [`WifiNetwork`], its SSID and password checked once
+/// read
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+struct WireWifiNetwork {
+    id: Ulid,
+    tags: Vec<Ulid>,
+    ssid: String,
+    security: WifiSecurity,
+    password: SecretString,
+    hidden: bool,
+}
+
+impl TryFrom<WireWifiNetwork> for WifiNetwork {
+    type Error = &'static str;
+
+    fn try_from(network: WireWifiNetwork) -> Result<Self, Self::Error> {
+        check_ssid(&network.ssid)
+            .and_then(|_| check_password(network.security, &network.password))
+            .map_err(|_| "invalid Wi-Fi network")?;
+
+        Ok(Self {
+            id: network.id,
+            tags: network.tags,
+            ssid: network.ssid,
+            security: network.security,
+            password: network.password,
+            hidden: network.hidden,
+        })
+    }
+}
+
+impl WifiNetwork {
+    /// Create the network `ssid`, using the `security` protocol with the
+    /// `password`
+    ///
+    /// # Errors
+    ///
+    /// Return [`MyKeyringError::InvalidWifiNetwork`] if the `ssid` is empty or
+    /// longer than 32 bytes, or if the `password` length does not match the
+    /// `security` protocol
+    pub fn new(ssid: &str, security: WifiSecurity, password: &str) -> crate::Result<Self> {
+        check_ssid(ssid)?;
+        check_password(security, password)?;
+
+        Ok(Self {
+            id: Ulid::new(),
+            tags: Vec::new(),
+            ssid: ssid.to_owned(),
+            security,
+            password: password.into(),
+            hidden: false,
+        })
+    }
+
+    /// Return the network name
+    #[inline]
+    pub fn ssid(&self) -> &str {
+        &self.ssid
+    }
+
+    /// Return the security protocol
+    #[inline]
+    pub fn security(&self) -> WifiSecurity {
+        self.security
+    }
+
+    /// Return the password, empty for the open networks
+    #[inline]
+    pub fn password(&self) -> &str {
+        &self.password
+    }
+
+    /// Change the security protocol and the password
+    ///
+    /// # Errors
+    ///
+    /// Return [`MyKeyringError::InvalidWifiNetwork`] if the `password` length
+    /// does not match the `security` protocol
+    pub fn set_password(&mut self, security: WifiSecurity, password: &str) -> crate::Result<()> {
+        check_password(security, password)?;
+        self.security = security;
+        self.password = password.into();
+
+        Ok(())
+    }
+
+    /// Return if the network does not broadcast its SSID
+    #[inline]
+    pub fn hidden(&self) -> bool {
+        self.hidden
+    }
+
+    /// Change if the network does not broadcast its SSID
+    #[inline]
+    pub fn set_hidden(&mut self, hidden: bool) {
+        self.hidden = hidden;
+    }
+}
+
+impl_item!(WifiNetwork);
+
+/// Check the `ssid` length
+fn check_ssid(ssid: &str) -> crate::Result<()> {
+    if ssid.is_empty() || ssid.len() > MAX_SSID_LENGTH {
+        return Err(MyKeyringError::InvalidWifiNetwork);
+    }
+
+    Ok(())
+}
+
+/// Check the `password` length, for the `security` protocol
+fn check_password(security: WifiSecurity, password: &str) -> crate::Result<()> {
+    let hex = password.bytes().all(|c| c.is_ascii_hexdigit());
+    let valid = match (security, password.len()) {
+        (WifiSecurity::Open, length) => length == 0,
+        (WifiSecurity::Wep, 5) | (WifiSecurity::Wep, 13) => true,
+        (WifiSecurity::Wep, 10) | (WifiSecurity::Wep, 26) => hex,
+        (WifiSecurity::Wpa, 8..=63) => password.is_ascii(),
+        (WifiSecurity::Wpa, 64) => hex,
+        _ => false,
+    };
+
+    if valid {
+        Ok(())
+    } else {
+        Err(MyKeyringError::InvalidWifiNetwork)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn password_lengths() {
+        assert!(WifiNetwork::new("Home", WifiSecurity::Wpa, "My_secr3tP@55w0rd").is_ok());
+        assert!(WifiNetwork::new("Cafe", WifiSecurity::Open, "").is_ok());
+        assert!(WifiNetwork::new("Old", WifiSecurity::Wep, "0123456789").is_ok());
+
+        for (ssid, security, password) in [
+            ("", WifiSecurity::Open, ""),
+            ("Home", WifiSecurity::Wpa, "short"),
+            ("Home", WifiSecurity::Wpa, &"z".repeat(64)),
+            ("Cafe", WifiSecurity::Open, "password"),
+            ("Old", WifiSecurity::Wep, "012345678g"),
+        ] {
+            assert_eq!(
+                WifiNetwork::new(ssid, security, password).unwrap_err(),
+                MyKeyringError::InvalidWifiNetwork
+            );
+        }
+    }
+
+    #[test]
+    fn checked_when_read() {
+        let mut network = WifiNetwork::new("Home", WifiSecurity::Wpa, "My_secr3tP@55w0rd").unwrap();
+        let read: WifiNetwork =
+            bincode::deserialize(&bincode::serialize(&network).unwrap()).unwrap();
+        assert_eq!(read.password(), network.password());
+
+        network.password = "short".into();
+        assert!(
+            bincode::deserialize::<WifiNetwork>(&bincode::serialize(&network).unwrap()).is_err()
+        );
+        network.password = "My_secr3tP@55w0rd".into();
+        network.ssid = String::new();
+        assert!(
+            bincode::deserialize::<WifiNetwork>(&bincode::serialize(&network).unwrap()).is_err()
+        );
+    }
+}
@@ -1,43 +1,119 @@
+//! Contains the `KeyRing`, holding the own `Secret`, the signing identity and
+//! the known `PublicKey` of the other devices
+
 use fnv::FnvHashMap;
 use rand_core::OsRng;
 use ulid::Ulid;
 
-use crate::{PublicKey, Secret, SharedSecret};
+pub use self::{
+    envelope::Envelope,
+    fingerprint::{safety_number, Fingerprint, FINGERPRINT_LENGTH, FINGERPRINT_WORDS},
+    identity::{
+        IdentityKey, IdentityPublicKey, Signature, IDENTITY_PUBLIC_LENGTH, IDENTITY_SECRET_LENGTH,
+        SIGNATURE_LENGTH,
+    },
+    rotation::{KeyId, KeyRotation, KEY_ID_LENGTH},
+    sealed::seal,
+};
+use crate::{secret::Locked, PublicKey, Secret, SharedSecret};
+
+mod backup;
+mod envelope;
+mod fingerprint;
+mod identity;
+mod rotation;
+mod sealed;
 
 /// The `PublicKey` pool holder and keep the `Secret`
 pub struct KeyRing {
-    /// My `Secret` key
-    my_key: Secret,
+    /// My current `Secret` key
+    my_key: Locked<Secret>,
+    /// Identifier of my current key
+    my_key_id: KeyId,
+    /// My previous keys, kept to decrypt the payloads encrypted for them
+    retired: FnvHashMap<KeyId, Locked<Secret>>,
+    /// My signing identity
+    identity: IdentityKey,
     /// The `PublicKey` pool
     known: FnvHashMap<Ulid, PublicKey>,
 }
 
+opaque_debug::implement!(KeyRing);
+
 impl KeyRing {
-    /// Generate a new `KeyRing`
+    /// Generate a new `KeyRing`, with a new random signing identity
+    ///
+    /// The secrets are locked in memory if possible, see
+    /// [`KeyRing::ensure_locked`].
     pub fn new(secret: Secret, known: FnvHashMap<Ulid, PublicKey>) -> Self {
+        Self::with_identity(secret, IdentityKey::generate(), known)
+    }
+
+    /// Generate a new `KeyRing`, with an existing signing `identity`
+    pub fn with_identity(
+        secret: Secret,
+        identity: IdentityKey,
+        known: FnvHashMap<Ulid, PublicKey>,
+    ) -> Self {
         Self {
-            my_key: secret,
+            my_key_id: KeyId::new(&PublicKey::from(&secret)),
+            my_key: Locked::new(secret),
+            retired: FnvHashMap::default(),
+            identity,
             known,
         }
     }
 
+    /// Check that my current and retired secrets, and my signing identity,
+    /// are locked in memory
+    ///
+    /// # Errors
+    ///
+    /// Return [`MyKeyringError::MemoryNotLocked`] if one of them may be
+    /// swapped to disk
+    ///
+    /// [`MyKeyringError::MemoryNotLocked`]: crate::MyKeyringError::MemoryNotLocked
+    pub fn ensure_locked(&self) -> crate::Result<()> {
+        self.my_key.ensure_locked()?;
+        self.identity.ensure_locked()?;
+        self.retired.values().try_for_each(Locked::ensure_locked)
+    }
+
+    /// Return the public key of my signing identity, to be given to the
+    /// devices and the server checking my signatures
+    #[inline]
+    pub fn identity_public_key(&self) -> IdentityPublicKey {
+        self.identity.public_key()
+    }
+
+    /// Sign the `message` with my identity, for the application specific
+    /// `context`, see [`IdentityKey::sign`]
+    #[inline]
+    pub fn sign(&self, message: &[u8], context: &[u8]) -> Signature {
+        self.identity.sign(message, context)
+    }
+
     /// Add a new known key to the `PublicKey` known pool
     pub fn add_known(&mut self, known_id: Ulid, key: PublicKey) {
         self.known.insert(known_id, key);
     }
 
+    /// Return the `Fingerprint` of my `PublicKey`, to be compared with the one
+    /// shown by the other device
+    pub fn fingerprint(&self) -> Fingerprint {
+        Fingerprint::new(&PublicKey::from(&*self.my_key))
+    }
+
     /// Remove the key from the pool
     pub fn del_known(&mut self, known_id: Ulid) {
         self.known.remove(&known_id);
     }
 
-    /// Return the associated `PublicKey` based on the `key_id` from the pool
-    pub fn get_shared_key(&self, key_id: Ulid) -> Option<SharedSecret> {
-        if let Some(public_key) = self.known.get(&key_id) {
-            self.my_key.as_diffie_hellman(public_key)
-        } else {
-            None
-        }
+    /// Return the `SharedSecret` with the `PublicKey` `key_id` from the pool,
+    /// kept out of the swap and wiped once dropped
+    pub fn get_shared_key(&self, key_id: Ulid) -> Option<Locked<SharedSecret>> {
+        let public_key = self.known.get(&key_id)?;
+        self.my_key.as_diffie_hellman(public_key).map(Locked::new)
     }
 
     /// Generate a `SharedSecret` based on an ephemeral private key that is not
@@ -46,43 +122,57 @@ impl KeyRing {
     /// It returns the associated `PublicKey` that must be used to compute the
     /// `shared_secret` on the other side so it must be send to the other side,
     /// and the `SharedSecret` computed from the `public_key` parameter and the
-    /// ephemeral private key.
+    /// ephemeral private key, kept out of the swap and wiped once dropped.
     ///
     /// It does some sanity check, and so compute as many ephemeral key as
     /// needed to obtain a valid `SharedKey`.
-    pub fn shared_with_ephemeral(&self, public_key: &PublicKey) -> (PublicKey, SharedSecret) {
-        loop {
-            let (public, shared) = self.gen_ephemeral(public_key);
-            if let Some(shared) = shared {
-                return (public, shared);
-            }
-        }
+    pub fn shared_with_ephemeral(
+        &self,
+        public_key: &PublicKey,
+    ) -> (PublicKey, Locked<SharedSecret>) {
+        ephemeral_shared(public_key)
     }
 
     /// Generate a `SharedSecret` from the `public_key` and the local
-    /// `PrivateKey`
+    /// `PrivateKey`, kept out of the swap and wiped once dropped
     ///
     /// The `SharedKey` computation is normally always valid here, sanity check
     /// already done in the [`shared_with_ephemeral`] step.
-    pub fn shared_from_public(&self, public_key: &PublicKey) -> SharedSecret {
-        self.my_key
-            .as_diffie_hellman(public_key)
-            .expect("shared secret")
+    pub fn shared_from_public(&self, public_key: &PublicKey) -> Locked<SharedSecret> {
+        Locked::new(
+            self.my_key
+                .as_diffie_hellman(public_key)
+                .expect("shared secret"),
+        )
     }
+}
 
-    /// Generate an ephemeral private key and it's associated public's, then
-    /// make the diffie hellman exchange with the ephemeral private and the
-    /// provided `public_key`
-    fn gen_ephemeral(&self, public_key: &PublicKey) -> (PublicKey, Option<SharedSecret>) {
-        let ephemeral_secret = Secret::new(&mut OsRng);
-        let ephemeral_public_key = PublicKey::from(&ephemeral_secret);
-        (
-            ephemeral_public_key,
-            ephemeral_secret.to_diffie_hellman(public_key),
-        )
+/// Generate a `SharedSecret` with the `public_key` and an ephemeral private
+/// key, as many times as needed to obtain a valid one, see
+/// [`KeyRing::shared_with_ephemeral`]
+pub(crate) fn ephemeral_shared(public_key: &PublicKey) -> (PublicKey, Locked<SharedSecret>) {
+    loop {
+        let (public, shared) = gen_ephemeral(public_key);
+        if let Some(shared) = shared {
+            return (public, shared);
+        }
     }
 }
 
+/// Generate an ephemeral private key and it's associated public's, then
+/// make the diffie hellman exchange with the ephemeral private and the
+/// provided `public_key`
+fn gen_ephemeral(public_key: &PublicKey) -> (PublicKey, Option<Locked<SharedSecret>>) {
+    let ephemeral_secret = Locked::new(Secret::new(&mut OsRng));
+    let ephemeral_public_key = PublicKey::from(&*ephemeral_secret);
+    (
+        ephemeral_public_key,
+        ephemeral_secret
+            .as_diffie_hellman(public_key)
+            .map(Locked::new),
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,179 @@
+use core::convert::{TryFrom, TryInto};
+
+use bincode::{deserialize, serialize};
+use ulid::Ulid;
+
+use crate::{
+    crypt::{crypt_with_password, decrypt_with_password, CryptedMessage, Kdf},
+    keys::{IdentityKey, KeyId, KeyRing},
+    secret::{Locked, SecretBytes},
+    MyKeyringError, PublicKey, Secret,
+};
+
+/// Application specific context of the `KeyRing` backup encryption
+const CONTEXT: &[u8] = b"my-keyring keyring backup";
+
+/// Serialised layout of a `KeyRing`: own secret, signing identity secret, the
+/// identifiers and public keys of the known pool, then the retired secrets
+type WireKeyRing = (
+    SecretBytes,
+    SecretBytes,
+    Vec<(u128, Vec<u8>)>,
+    Vec<SecretBytes>,
+);
+
+impl KeyRing {
+    /// Serialise the own current and retired `Secret`, the signing identity
+    /// and all the known `PublicKey`, with their identifiers, encrypted with
+    /// the `password` stretched by `kdf`, defaulting to Argon2id
+    ///
+    /// The result is a serialised [`CryptedMessage`], to be read back with
+    /// [`KeyRing::import`], to back up and restore the identity of a device.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> my_keyring_shared::Result<()> {
+    /// use my_keyring_shared::{crypt::Kdf, keys::KeyRing, PublicKey, Secret};
+    /// use rand_core::OsRng;
+    /// use ulid::Ulid;
+    ///
+    /// let mut keyring = KeyRing::new(Secret::new(&mut OsRng), Default::default());
+    /// let laptop = Ulid::new();
+    /// keyring.add_known(laptop, PublicKey::from(&Secret::new(&mut OsRng)));
+    ///
+    /// # let kdf = Kdf::Pbkdf2 { iterations: 1_000 };
+    /// let backup = keyring.export(b"My_secr3tP@55w0rd", kdf)?;
+    ///
+    /// let restored = KeyRing::import(&backup, b"My_secr3tP@55w0rd")?;
+    /// assert!(restored.get_shared_key(laptop).is_some());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Return [`MyKeyringError::InvalidKdfParameters`] if the `kdf` parameters
+    /// are out of range
+    pub fn export(&self, password: &[u8], kdf: impl Into<Option<Kdf>>) -> crate::Result<Vec<u8>> {
+        let known = self
+            .known
+            .iter()
+            .map(|(id, key)| (u128::from(*id), key.as_bytes().to_vec()))
+            .collect();
+        let wire: WireKeyRing = (
+            self.my_key.as_bytes().to_vec().into(),
+            self.identity.as_bytes().to_vec().into(),
+            known,
+            self.retired
+                .values()
+                .map(|secret| secret.as_bytes().to_vec().into())
+                .collect(),
+        );
+        let data = SecretBytes::from(serialize(&wire).expect("serialized keyring"));
+
+        Ok(crypt_with_password(password, &data, kdf, Some(CONTEXT), None)?.into())
+    }
+
+    /// Decrypt a `KeyRing` serialised by [`KeyRing::export`] with the
+    /// `password`
+    ///
+    /// # Errors
+    ///
+    /// Return [`MyKeyringError::InvalidCryptedMessage`] if `data` is not an
+    /// exported `KeyRing`, [`MyKeyringError::IncorrectHmac`] if the `password`
+    /// is wrong or the data has been altered, and [`MyKeyringError::DHError`]
+    /// if a key is invalid
+    pub fn import(data: &[u8], password: &[u8]) -> crate::Result<Self> {
+        let encrypted = CryptedMessage::try_from(data)?;
+        let data = decrypt_with_password(password, encrypted, Some(CONTEXT), None)?;
+        let (secret, identity, known, retired): WireKeyRing =
+            deserialize(&data).map_err(|_| MyKeyringError::InvalidCryptedMessage)?;
+
+        let secret = Secret::from_bytes(&secret).ok_or(MyKeyringError::DHError)?;
+        let identity = IdentityKey::from_bytes(
+            identity[..]
+                .try_into()
+                .map_err(|_| MyKeyringError::InvalidKeyLength)?,
+        );
+        let known = known
+            .into_iter()
+            .map(|(id, key)| {
+                let key = PublicKey::from_bytes(&key).ok_or(MyKeyringError::DHError)?;
+                Ok((Ulid::from(id), key))
+            })
+            .collect::<crate::Result<_>>()?;
+
+        let mut keyring = Self::with_identity(secret, identity, known);
+        for secret in retired {
+            let secret = Secret::from_bytes(&secret).ok_or(MyKeyringError::DHError)?;
+            keyring
+                .retired
+                .insert(KeyId::new(&PublicKey::from(&secret)), Locked::new(secret));
+        }
+
+        Ok(keyring)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_core::OsRng;
+
+    use super::*;
+
+    const PASSWORD: &[u8] = b"My_secr3tP@55w0rd";
+    const KDF: Kdf = Kdf::Pbkdf2 { iterations: 10 };
+
+    #[test]
+    fn export_then_import() -> crate::Result<()> {
+        let mut keyring = KeyRing::new(Secret::new(&mut OsRng), Default::default());
+        keyring.rotate();
+        let public = PublicKey::from(&*keyring.my_key);
+        let ids: Vec<Ulid> = (0..3)
+            .map(|_| {
+                let id = Ulid::new();
+                keyring.add_known(id, PublicKey::from(&Secret::new(&mut OsRng)));
+                id
+            })
+            .collect();
+
+        let restored = KeyRing::import(&keyring.export(PASSWORD, KDF)?, PASSWORD)?;
+
+        assert_eq!(restored.known.len(), 3);
+        for id in ids {
+            assert_eq!(
+                restored.get_shared_key(id).unwrap().as_bytes(),
+                keyring.get_shared_key(id).unwrap().as_bytes()
+            );
+        }
+        assert_eq!(
+            restored.identity_public_key(),
+            keyring.identity_public_key()
+        );
+        // Same own secret
+        assert_eq!(
+            PublicKey::from(&*restored.my_key).as_bytes(),
+            public.as_bytes()
+        );
+        assert_eq!(
+            restored.retired_keys().collect::<Vec<_>>(),
+            keyring.retired_keys().collect::<Vec<_>>()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn import_wrong_password() -> crate::Result<()> {
+        let keyring = KeyRing::new(Secret::new(&mut OsRng), Default::default());
+        let backup = keyring.export(PASSWORD, KDF)?;
+
+        assert_eq!(
+            KeyRing::import(&backup, b"password").unwrap_err(),
+            MyKeyringError::IncorrectHmac
+        );
+
+        Ok(())
+    }
+}
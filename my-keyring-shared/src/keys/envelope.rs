@@ -0,0 +1,298 @@
+use core::convert::TryFrom;
+
+use bincode::{deserialize, serialize};
+use fnv::FnvHashMap;
+use rand_core::{OsRng, RngCore};
+use ulid::Ulid;
+
+use crate::{
+    crypt::{crypt, crypt_with_key, decrypt, decrypt_with_key, CryptedMessage, KEY_LENGTH},
+    keys::{KeyId, KeyRing, KEY_ID_LENGTH},
+    secret::{Locked, SecretBytes},
+    MyKeyringError, PublicKey,
+};
+
+/// Magic number at the beginning of every serialised envelope
+const MAGIC: &[u8; 4] = b"MKCE";
+/// Current version of the envelope format
+const VERSION: u8 = 1;
+/// Application specific context of the content key wrapping
+const WRAP_CONTEXT: &[u8] = b"my-keyring envelope key";
+/// Application specific context of the content encryption
+const CONTENT_CONTEXT: &[u8] = b"my-keyring envelope content";
+
+/// Serialised layout of the recipients: identifier, [`KeyId`] of its key,
+/// ephemeral public key and wrapped content key
+type WireRecipients = Vec<(u128, [u8; KEY_ID_LENGTH], Vec<u8>, Vec<u8>)>;
+
+/// Data encrypted once, for several recipients
+///
+/// The data is encrypted with a random content key, which is wrapped for each
+/// recipient with a secret shared between its `PublicKey` and an ephemeral key.
+pub struct Envelope {
+    /// Key identifier, ephemeral public key and wrapped content key, by
+    /// recipient identifier
+    recipients: FnvHashMap<Ulid, (KeyId, PublicKey, CryptedMessage)>,
+    /// Data encrypted with the content key
+    message: CryptedMessage,
+}
+
+opaque_debug::implement!(Envelope);
+
+impl Envelope {
+    /// List the identifiers of the recipients, in no particular order
+    pub fn recipients(&self) -> impl Iterator<Item = Ulid> + '_ {
+        self.recipients.keys().copied()
+    }
+}
+
+impl KeyRing {
+    /// Encrypt `data` once, for all the `recipients` identifiers of the known
+    /// `PublicKey` pool, with optional `aad` associated data
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> my_keyring_shared::Result<()> {
+    /// use my_keyring_shared::{keys::KeyRing, PublicKey, Secret};
+    /// use rand_core::OsRng;
+    /// use ulid::Ulid;
+    ///
+    /// let phone = Secret::new(&mut OsRng);
+    /// let (phone_id, laptop_id) = (Ulid::new(), Ulid::new());
+    ///
+    /// let mut keyring = KeyRing::new(Secret::new(&mut OsRng), Default::default());
+    /// keyring.add_known(phone_id, PublicKey::from(&phone));
+    /// # keyring.add_known(laptop_id, PublicKey::from(&Secret::new(&mut OsRng)));
+    ///
+    /// let envelope = keyring.encrypt_for(&[phone_id, laptop_id], b"Lorem ipsum", None)?;
+    ///
+    /// // On the phone
+    /// let phone = KeyRing::new(phone, Default::default());
+    /// assert_eq!(
+    ///     phone.decrypt_envelope(phone_id, &envelope, None)?,
+    ///     b"Lorem ipsum"
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Return [`MyKeyringError::UnknownRecipient`] if a recipient is not in the
+    /// known pool, and [`MyKeyringError::DataLengthExceeded`] if `data` is too
+    /// big to be encrypted
+    pub fn encrypt_for(
+        &self,
+        recipients: &[Ulid],
+        data: &[u8],
+        aad: Option<&[u8]>,
+    ) -> crate::Result<Envelope> {
+        let mut content_key = Locked::new([0; KEY_LENGTH]);
+        OsRng.fill_bytes(&mut *content_key);
+
+        let mut wrapped = FnvHashMap::default();
+        for &recipient in recipients {
+            let public_key = self
+                .known
+                .get(&recipient)
+                .ok_or(MyKeyringError::UnknownRecipient)?;
+            let (ephemeral, shared) = self.shared_with_ephemeral(public_key);
+            // The identifier is authenticated, a wrapped key cannot be moved
+            let key = crypt(
+                &shared,
+                &content_key[..],
+                Some(WRAP_CONTEXT),
+                Some(&u128::from(recipient).to_be_bytes()),
+            )?;
+            wrapped.insert(recipient, (KeyId::new(public_key), ephemeral, key));
+        }
+
+        Ok(Envelope {
+            recipients: wrapped,
+            message: crypt_with_key(&content_key[..], None, data, Some(CONTENT_CONTEXT), aad)?,
+        })
+    }
+
+    /// Decrypt an `envelope` encrypted by [`KeyRing::encrypt_for`], where this
+    /// `KeyRing` current or retired secret is the one of the recipient
+    /// `recipient_id`, with the optional `aad` associated data used to encrypt
+    /// it
+    ///
+    /// # Errors
+    ///
+    /// Return [`MyKeyringError::UnknownRecipient`] if the `envelope` has not
+    /// been encrypted for `recipient_id`, or for one of my keys,
+    /// [`MyKeyringError::DHError`] if the
+    /// ephemeral key is invalid, and [`MyKeyringError::IncorrectHmac`] if this
+    /// secret or the `aad` is not valid, or if the `envelope` has been altered
+    pub fn decrypt_envelope(
+        &self,
+        recipient_id: Ulid,
+        envelope: &Envelope,
+        aad: Option<&[u8]>,
+    ) -> crate::Result<SecretBytes> {
+        let (key_id, ephemeral, key) = envelope
+            .recipients
+            .get(&recipient_id)
+            .ok_or(MyKeyringError::UnknownRecipient)?;
+        let shared = self
+            .secret(*key_id)?
+            .as_diffie_hellman(ephemeral)
+            .map(Locked::new)
+            .ok_or(MyKeyringError::DHError)?;
+        let content_key = decrypt(
+            &shared,
+            key.clone(),
+            Some(WRAP_CONTEXT),
+            Some(&u128::from(recipient_id).to_be_bytes()),
+        )?;
+        if content_key.len() != KEY_LENGTH {
+            return Err(MyKeyringError::InvalidKeyLength);
+        }
+
+        decrypt_with_key(
+            &content_key,
+            envelope.message.clone(),
+            Some(CONTENT_CONTEXT),
+            aad,
+        )
+    }
+}
+
+impl TryFrom<&[u8]> for Envelope {
+    type Error = MyKeyringError;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        if value.len() < MAGIC.len() + 1 || &value[..MAGIC.len()] != MAGIC {
+            return Err(MyKeyringError::InvalidCryptedMessage);
+        }
+        if value[MAGIC.len()] != VERSION {
+            return Err(MyKeyringError::UnsupportedCryptedMessageVersion);
+        }
+        let (wire, message): (WireRecipients, Vec<u8>) = deserialize(&value[MAGIC.len() + 1..])
+            .map_err(|_| MyKeyringError::InvalidCryptedMessage)?;
+        let recipients = wire
+            .into_iter()
+            .map(|(id, key_id, ephemeral, key)| {
+                let key_id = KeyId::from(key_id);
+                let ephemeral = PublicKey::from_bytes(&ephemeral).ok_or(MyKeyringError::DHError)?;
+                Ok((
+                    Ulid::from(id),
+                    (key_id, ephemeral, CryptedMessage::try_from(key)?),
+                ))
+            })
+            .collect::<crate::Result<_>>()?;
+
+        Ok(Self {
+            recipients,
+            message: CryptedMessage::try_from(message)?,
+        })
+    }
+}
+
+impl TryFrom<Vec<u8>> for Envelope {
+    type Error = MyKeyringError;
+
+    fn try_from(value: Vec<u8>) -> Result<Self, Self::Error> {
+        Self::try_from(&value[..])
+    }
+}
+
+impl From<Envelope> for Vec<u8> {
+    fn from(envelope: Envelope) -> Self {
+        let wire: WireRecipients = envelope
+            .recipients
+            .into_iter()
+            .map(|(id, (key_id, ephemeral, key))| {
+                (
+                    u128::from(id),
+                    *key_id.as_bytes(),
+                    ephemeral.as_bytes().to_vec(),
+                    key.into(),
+                )
+            })
+            .collect();
+        let message: Vec<u8> = envelope.message.into();
+
+        let mut data = MAGIC.to_vec();
+        data.push(VERSION);
+        data.extend(serialize(&(wire, message)).expect("serialized envelope"));
+        data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Secret;
+
+    fn keyrings() -> (KeyRing, Vec<(Ulid, KeyRing)>) {
+        let mut sender = KeyRing::new(Secret::new(&mut OsRng), Default::default());
+        let devices = (0..3)
+            .map(|_| {
+                let id = Ulid::new();
+                let secret = Secret::new(&mut OsRng);
+                sender.add_known(id, PublicKey::from(&secret));
+                (id, KeyRing::new(secret, Default::default()))
+            })
+            .collect();
+
+        (sender, devices)
+    }
+
+    #[test]
+    fn every_recipient_decrypts() -> crate::Result<()> {
+        let (sender, devices) = keyrings();
+        let ids: Vec<Ulid> = devices.iter().map(|(id, _)| *id).collect();
+
+        let envelope = sender.encrypt_for(&ids, b"Lorem ipsum", Some(b"a"))?;
+        // Through the serialised form
+        let envelope = Envelope::try_from(Vec::from(envelope))?;
+        assert_eq!(envelope.recipients().count(), 3);
+
+        for (id, device) in &devices {
+            assert_eq!(
+                device.decrypt_envelope(*id, &envelope, Some(b"a"))?,
+                b"Lorem ipsum"
+            );
+            assert_eq!(
+                device.decrypt_envelope(*id, &envelope, None).unwrap_err(),
+                MyKeyringError::IncorrectHmac
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn not_a_recipient() -> crate::Result<()> {
+        let (sender, devices) = keyrings();
+        let (first, device) = &devices[0];
+        let (second, _) = &devices[1];
+
+        assert_eq!(
+            sender
+                .encrypt_for(&[*first, Ulid::new()], b"Lorem ipsum", None)
+                .unwrap_err(),
+            MyKeyringError::UnknownRecipient
+        );
+
+        let envelope = sender.encrypt_for(&[*second], b"Lorem ipsum", None)?;
+        assert_eq!(
+            device
+                .decrypt_envelope(*first, &envelope, None)
+                .unwrap_err(),
+            MyKeyringError::UnknownRecipient
+        );
+        // Using the identifier of another recipient, encrypted for its key
+        assert_eq!(
+            device
+                .decrypt_envelope(*second, &envelope, None)
+                .unwrap_err(),
+            MyKeyringError::UnknownRecipient
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,185 @@
+use core::fmt;
+
+use crate::PublicKey;
+
+/// Length of a fingerprint, in bytes
+pub const FINGERPRINT_LENGTH: usize = 32;
+/// Number of words rendered by [`Fingerprint::to_words`], a word per byte
+pub const FINGERPRINT_WORDS: usize = 8;
+/// Number of digits of each half of a safety number
+const SAFETY_DIGITS: usize = 30;
+/// Context of the fingerprint derivation
+const FINGERPRINT_CONTEXT: &str = "my-keyring 2021-04 public key fingerprint";
+/// Context of the safety number derivation
+const SAFETY_CONTEXT: &str = "my-keyring 2021-04 public key safety number";
+/// 256 words, one for each byte value
+const WORDS: &str = include_str!("words.txt");
+
+/// Digest of a `PublicKey`, to be compared by the users when pairing devices
+///
+/// # Examples
+///
+/// ```
+/// use my_keyring_shared::{keys::Fingerprint, PublicKey, Secret};
+/// use rand_core::OsRng;
+///
+/// let public = PublicKey::from(&Secret::new(&mut OsRng));
+/// let fingerprint = Fingerprint::new(&public);
+///
+/// // "3FA2 9C01 ...", 16 groups
+/// println!("{}", fingerprint);
+/// // "lotus-acid-...", 8 words
+/// println!("{}", fingerprint.to_words().join("-"));
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Fingerprint([u8; FINGERPRINT_LENGTH]);
+
+impl Fingerprint {
+    /// Compute the fingerprint of the `public_key`
+    pub fn new(public_key: &PublicKey) -> Self {
+        let mut fingerprint = [0; FINGERPRINT_LENGTH];
+        blake3::derive_key(FINGERPRINT_CONTEXT, public_key.as_bytes(), &mut fingerprint);
+        Self(fingerprint)
+    }
+
+    /// Return the raw fingerprint
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8; FINGERPRINT_LENGTH] {
+        &self.0
+    }
+
+    /// Render the fingerprint as uppercase hexadecimal, in groups of 4
+    /// characters separated by a space
+    pub fn to_hex(&self) -> String {
+        self.0
+            .chunks(2)
+            .map(|group| format!("{:02X}{:02X}", group[0], group[1]))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Render the [`FINGERPRINT_WORDS`] first bytes of the fingerprint as
+    /// words, easier to read aloud
+    pub fn to_words(&self) -> Vec<&'static str> {
+        self.0[..FINGERPRINT_WORDS]
+            .iter()
+            .map(|&byte| WORDS.lines().nth(usize::from(byte)).expect("256 words"))
+            .collect()
+    }
+}
+
+impl fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_hex())
+    }
+}
+
+/// Compute the numeric safety number of a pair of keys, 60 digits in groups of
+/// 5
+///
+/// Each key gives 30 digits, sorted so both devices show the same number,
+/// whatever the order of the keys.
+///
+/// # Examples
+///
+/// ```
+/// use my_keyring_shared::{keys::safety_number, PublicKey, Secret};
+/// use rand_core::OsRng;
+///
+/// let phone = PublicKey::from(&Secret::new(&mut OsRng));
+/// let browser = PublicKey::from(&Secret::new(&mut OsRng));
+///
+/// assert_eq!(
+///     safety_number(&phone, &browser),
+///     safety_number(&browser, &phone)
+/// );
+/// ```
+pub fn safety_number(key: &PublicKey, other: &PublicKey) -> String {
+    let mut halves = [safety_digits(key), safety_digits(other)];
+    halves.sort();
+
+    halves
+        .concat()
+        .as_bytes()
+        .chunks(5)
+        .map(|group| core::str::from_utf8(group).expect("ascii digits"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Compute the 30 digits of the safety number half of a `key`, 5 digits from
+/// every 5 bytes of its digest
+fn safety_digits(key: &PublicKey) -> String {
+    let mut digest = [0; SAFETY_DIGITS];
+    blake3::derive_key(SAFETY_CONTEXT, key.as_bytes(), &mut digest);
+
+    digest
+        .chunks(5)
+        .map(|chunk| {
+            let value = chunk
+                .iter()
+                .fold(0_u64, |value, &byte| value << 8 | u64::from(byte));
+            format!("{:05}", value % 100_000)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_core::OsRng;
+
+    use super::*;
+    use crate::Secret;
+
+    #[test]
+    fn words_list() {
+        let words: Vec<&str> = WORDS.lines().collect();
+        assert_eq!(words.len(), 256);
+
+        let mut unique = words.clone();
+        unique.sort_unstable();
+        unique.dedup();
+        assert_eq!(unique, words);
+    }
+
+    #[test]
+    fn renderings() {
+        let public = PublicKey::from(&Secret::new(&mut OsRng));
+        let fingerprint = Fingerprint::new(&public);
+
+        // Same on every device
+        assert_eq!(fingerprint, Fingerprint::new(&public));
+
+        let hex = fingerprint.to_hex();
+        assert_eq!(hex.len(), 16 * 4 + 15);
+        assert_eq!(hex, fingerprint.to_string());
+        assert!(hex.starts_with(&format!("{:02X}", fingerprint.as_bytes()[0])));
+
+        let words = fingerprint.to_words();
+        assert_eq!(words.len(), FINGERPRINT_WORDS);
+        assert_eq!(
+            words[0],
+            WORDS
+                .lines()
+                .nth(usize::from(fingerprint.as_bytes()[0]))
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn safety_number_of_pair() {
+        let phone = PublicKey::from(&Secret::new(&mut OsRng));
+        let browser = PublicKey::from(&Secret::new(&mut OsRng));
+        let other = PublicKey::from(&Secret::new(&mut OsRng));
+
+        let number = safety_number(&phone, &browser);
+        assert_eq!(number, safety_number(&browser, &phone));
+        assert_ne!(number, safety_number(&phone, &other));
+
+        let groups: Vec<&str> = number.split(' ').collect();
+        assert_eq!(groups.len(), 12);
+        assert!(groups
+            .iter()
+            .all(|group| group.len() == 5 && group.bytes().all(|c| c.is_ascii_digit())));
+    }
+}
@@ -0,0 +1,431 @@
+//! Ed448 signatures, as defined in RFC 8032, to authenticate who issued a
+//! message
+//!
+//! The curve arithmetic comes from `ed448-goldilocks`, and SHAKE256 from
+//! `sha3`.
+
+use core::{
+    convert::TryFrom,
+    ptr,
+    sync::atomic::{compiler_fence, Ordering},
+};
+
+use ed448_goldilocks::{
+    curve::edwards::{CompressedEdwardsY, ExtendedPoint},
+    Scalar,
+};
+use rand_core::{OsRng, RngCore};
+use sha3::{
+    digest::{ExtendableOutput, Update, XofReader},
+    Shake256,
+};
+use zeroize::Zeroize;
+
+use crate::{secret::Locked, MyKeyringError};
+
+/// Length of an Ed448 secret key
+pub const IDENTITY_SECRET_LENGTH: usize = 57;
+/// Length of an Ed448 public key
+pub const IDENTITY_PUBLIC_LENGTH: usize = 57;
+/// Length of an Ed448 signature
+pub const SIGNATURE_LENGTH: usize = 114;
+/// Length of the SHAKE256 output used by Ed448
+const DIGEST_LENGTH: usize = 114;
+
+/// Secret signing key, identifying a device
+pub struct IdentityKey {
+    /// Secret material, kept out of the swap and wiped once dropped
+    secret: Locked<IdentitySecret>,
+    /// Public key associated
+    public: IdentityPublicKey,
+}
+
+/// Secret material of an [`IdentityKey`]
+struct IdentitySecret {
+    /// Secret, as generated
+    secret: [u8; IDENTITY_SECRET_LENGTH],
+    /// Secret scalar, derived from the secret
+    scalar: Scalar,
+    /// Second half of the secret digest, used to derive the nonces
+    prefix: [u8; IDENTITY_SECRET_LENGTH],
+}
+
+impl Zeroize for IdentitySecret {
+    fn zeroize(&mut self) {
+        self.secret.zeroize();
+        wipe_scalar(&mut self.scalar);
+        self.prefix.zeroize();
+    }
+}
+
+impl Drop for IdentitySecret {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+opaque_debug::implement!(IdentityKey);
+
+/// Public verifying key of an [`IdentityKey`]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct IdentityPublicKey([u8; IDENTITY_PUBLIC_LENGTH]);
+
+/// Ed448 signature
+#[derive(Clone, Copy)]
+pub struct Signature([u8; SIGNATURE_LENGTH]);
+
+opaque_debug::implement!(Signature);
+
+impl IdentityKey {
+    /// Generate a new random identity
+    pub fn generate() -> Self {
+        let mut secret = [0; IDENTITY_SECRET_LENGTH];
+        OsRng.fill_bytes(&mut secret);
+        Self::from_bytes(secret)
+    }
+
+    /// Rebuild an identity from its `secret` bytes
+    ///
+    /// The `secret` given is wiped, the identity keeping its own copy.
+    pub fn from_bytes(mut secret: [u8; IDENTITY_SECRET_LENGTH]) -> Self {
+        let mut identity = Locked::new(IdentitySecret {
+            secret,
+            scalar: Scalar::zero(),
+            prefix: [0; IDENTITY_SECRET_LENGTH],
+        });
+        secret.zeroize();
+
+        let mut digest = Locked::new([0; DIGEST_LENGTH]);
+        shake256(&[&identity.secret], &mut *digest);
+
+        // Clamp the first half
+        let mut scalar = Locked::new([0; IDENTITY_SECRET_LENGTH]);
+        scalar.copy_from_slice(&digest[..IDENTITY_SECRET_LENGTH]);
+        scalar[0] &= 0xfc;
+        scalar[56] = 0;
+        scalar[55] |= 0x80;
+        identity.scalar = scalar_from_bytes(&scalar[..]);
+        identity
+            .prefix
+            .copy_from_slice(&digest[IDENTITY_SECRET_LENGTH..]);
+
+        let public = IdentityPublicKey(encode_point(
+            &ExtendedPoint::generator().scalar_mul(&identity.scalar),
+        ));
+
+        Self {
+            secret: identity,
+            public,
+        }
+    }
+
+    /// Return the secret bytes, to store the identity
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8; IDENTITY_SECRET_LENGTH] {
+        &self.secret.secret
+    }
+
+    /// Check that the secret material is locked in memory
+    ///
+    /// # Errors
+    ///
+    /// Return [`MyKeyringError::MemoryNotLocked`] if it may be swapped to disk
+    #[inline]
+    pub fn ensure_locked(&self) -> crate::Result<()> {
+        self.secret.ensure_locked()
+    }
+
+    /// Return the public key, to be given to the verifiers
+    #[inline]
+    pub fn public_key(&self) -> IdentityPublicKey {
+        self.public
+    }
+
+    /// Sign the `message`, for the application specific `context`, at most
+    /// 255 bytes long
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> my_keyring_shared::Result<()> {
+    /// use my_keyring_shared::keys::IdentityKey;
+    ///
+    /// let identity = IdentityKey::generate();
+    /// let signature = identity.sign(b"Lorem ipsum", b"example");
+    ///
+    /// identity
+    ///     .public_key()
+    ///     .verify(b"Lorem ipsum", b"example", &signature)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panic if the `context` is longer than 255 bytes
+    pub fn sign(&self, message: &[u8], context: &[u8]) -> Signature {
+        let dom = dom4(context);
+
+        // The nonce is as secret as the key
+        let mut digest = Locked::new([0; DIGEST_LENGTH]);
+        shake256(&[&dom, &self.secret.prefix, message], &mut *digest);
+        let mut r = scalar_from_bytes(&digest[..]);
+        let big_r = encode_point(&ExtendedPoint::generator().scalar_mul(&r));
+
+        shake256(&[&dom, &big_r, &self.public.0, message], &mut *digest);
+        let k = scalar_from_bytes(&digest[..]);
+        let s = r + k * self.secret.scalar;
+        wipe_scalar(&mut r);
+
+        let mut signature = [0; SIGNATURE_LENGTH];
+        signature[..IDENTITY_PUBLIC_LENGTH].copy_from_slice(&big_r);
+        signature[IDENTITY_PUBLIC_LENGTH..SIGNATURE_LENGTH - 1].copy_from_slice(&s.to_bytes());
+        Signature(signature)
+    }
+}
+
+impl IdentityPublicKey {
+    /// Return the public key bytes
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8; IDENTITY_PUBLIC_LENGTH] {
+        &self.0
+    }
+
+    /// Check the `signature` of the `message`, for the application specific
+    /// `context`
+    ///
+    /// # Errors
+    ///
+    /// Return [`MyKeyringError::InvalidSignature`] if the `signature` has not
+    /// been issued by this key for this `message` and `context`
+    pub fn verify(
+        &self,
+        message: &[u8],
+        context: &[u8],
+        signature: &Signature,
+    ) -> crate::Result<()> {
+        let (big_r, s) = signature.0.split_at(IDENTITY_PUBLIC_LENGTH);
+        let a = decode_point(&self.0).ok_or(MyKeyringError::InvalidSignature)?;
+        let r = decode_point(big_r).ok_or(MyKeyringError::InvalidSignature)?;
+
+        // S must be canonical, lower than the group order
+        let scalar_s = scalar_from_bytes(s);
+        if s[SIGNATURE_LENGTH - IDENTITY_PUBLIC_LENGTH - 1] != 0
+            || scalar_s.to_bytes()[..] != s[..56]
+        {
+            return Err(MyKeyringError::InvalidSignature);
+        }
+
+        let mut digest = [0; DIGEST_LENGTH];
+        shake256(&[&dom4(context), big_r, &self.0, message], &mut digest);
+        let k = scalar_from_bytes(&digest);
+
+        // [4][S]B = [4]R + [4][k]A
+        let left = ExtendedPoint::generator().scalar_mul(&scalar_s);
+        let right = r.add(&a.scalar_mul(&k));
+        if left.double().double() == right.double().double() {
+            Ok(())
+        } else {
+            Err(MyKeyringError::InvalidSignature)
+        }
+    }
+}
+
+impl TryFrom<&[u8]> for IdentityPublicKey {
+    type Error = MyKeyringError;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        let mut key = [0; IDENTITY_PUBLIC_LENGTH];
+        if value.len() != IDENTITY_PUBLIC_LENGTH {
+            return Err(MyKeyringError::InvalidKeyLength);
+        }
+        key.copy_from_slice(value);
+        decode_point(&key).ok_or(MyKeyringError::InvalidSignature)?;
+        Ok(Self(key))
+    }
+}
+
+impl Signature {
+    /// Return the signature bytes
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8; SIGNATURE_LENGTH] {
+        &self.0
+    }
+}
+
+impl TryFrom<&[u8]> for Signature {
+    type Error = MyKeyringError;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        let mut signature = [0; SIGNATURE_LENGTH];
+        if value.len() != SIGNATURE_LENGTH {
+            return Err(MyKeyringError::InvalidSignature);
+        }
+        signature.copy_from_slice(value);
+        Ok(Self(signature))
+    }
+}
+
+/// Prefix of every hashed data, with the signature `context`
+fn dom4(context: &[u8]) -> Vec<u8> {
+    assert!(context.len() <= 255, "context longer than 255 bytes");
+    let mut dom = b"SigEd448\x00".to_vec();
+    dom.push(context.len() as u8);
+    dom.extend_from_slice(context);
+    dom
+}
+
+/// Reduce the little endian `bytes`, at most [`DIGEST_LENGTH`] long, modulo
+/// the group order
+fn scalar_from_bytes(bytes: &[u8]) -> Scalar {
+    // 2^224, and every 28 bytes chunk, are lower than the order
+    let mut base = [0; 56];
+    base[28] = 1;
+    let base = Scalar::from_bytes(base);
+
+    bytes
+        .chunks(28)
+        .rev()
+        .fold(Scalar::zero(), |scalar, chunk| {
+            let mut limb = [0; 56];
+            limb[..chunk.len()].copy_from_slice(chunk);
+            scalar * base + Scalar::from_bytes(limb)
+        })
+}
+
+/// Encode a point as defined in RFC 8032: `y`, and the sign of `x` in the most
+/// significant bit
+fn encode_point(point: &ExtendedPoint) -> [u8; IDENTITY_PUBLIC_LENGTH] {
+    let CompressedEdwardsY(mut encoded) = point.compress();
+    // The library stores the sign in the lowest bit of the last byte
+    encoded[56] = (encoded[56] & 1) << 7;
+    encoded
+}
+
+/// Decode a point encoded with [`encode_point`], rejecting non canonical
+/// encodings
+fn decode_point(encoded: &[u8]) -> Option<ExtendedPoint> {
+    let mut bytes = [0; IDENTITY_PUBLIC_LENGTH];
+    bytes.copy_from_slice(encoded);
+    if bytes[56] & 0x7f != 0 {
+        return None;
+    }
+
+    let point = CompressedEdwardsY(bytes).decompress()?;
+    // Encoding it back checks both the sign convention and the canonical `y`
+    [point, point.negate()]
+        .iter()
+        .copied()
+        .find(|point| encode_point(point) == bytes)
+}
+
+/// Compute SHAKE256 of the concatenated `input` parts, filling the `output`
+fn shake256(input: &[&[u8]], output: &mut [u8]) {
+    let mut hasher = Shake256::default();
+    for part in input {
+        hasher.update(part);
+    }
+    hasher.finalize_xof().read(output);
+}
+
+/// Wipe a secret `scalar`, which does not implement `Zeroize`
+fn wipe_scalar(scalar: &mut Scalar) {
+    // Volatile, like `Zeroize`, so the write is not optimised out
+    unsafe { ptr::write_volatile(scalar, Scalar::zero()) };
+    compiler_fence(Ordering::SeqCst);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(data: &str) -> Vec<u8> {
+        (0..data.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&data[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn shake256_vectors() {
+        let mut output = [0; 8];
+        shake256(&[], &mut output);
+        assert_eq!(output.to_vec(), hex("46b9dd2b0ba88d13"));
+
+        // Longer than the rate, split in parts
+        let mut output = [0; 200];
+        shake256(&[&[0xa3; 100], &[0xa3; 100]], &mut output);
+        let mut whole = [0; 200];
+        shake256(&[&[0xa3; 200]], &mut whole);
+        assert_eq!(output[..], whole[..]);
+        assert_eq!(
+            output[..16].to_vec(),
+            hex("cd8a920ed141aa0407a22d59288652e9")
+        );
+    }
+
+    #[test]
+    fn ed448_vectors() -> crate::Result<()> {
+        let mut secret = [0; IDENTITY_SECRET_LENGTH];
+        secret
+            .iter_mut()
+            .enumerate()
+            .for_each(|(i, byte)| *byte = i as u8);
+        let identity = IdentityKey::from_bytes(secret);
+
+        assert_eq!(
+            identity.public_key().as_bytes().to_vec(),
+            hex("18d0a70e42a742dfb561279893385061d7b4dad8f6feed4791eaab66b2f4a4f02fc09462a8bfb1842d0bac60e8a1b3e55ba2407f33226f3800")
+        );
+
+        let signature = identity.sign(b"", b"");
+        assert_eq!(
+            signature.as_bytes().to_vec(),
+            hex("cb682b115cf0f0b0cf2a068acba2d0495714f2a50832739af364191c611f6983890ee133a4bf75ed2d09adc5d70f6d256b0806f3224b35d7802748b7cf55f5e9583df9f8c85db809f4877191c99ed0670ad62f54d63d7d35fddfd85efbad63554ff3ce9b847607b2f79181020880f13c1b00")
+        );
+        identity.public_key().verify(b"", b"", &signature)?;
+
+        let signature = identity.sign(b"abc", b"foo");
+        assert_eq!(
+            signature.as_bytes().to_vec(),
+            hex("d28eb65167ed925c658fe98f53d4102085370119c9190e12cb68e897bfcdc2be96c09d763ccd111d7c6e8919d4c7cccb3256068ed8f2f72c80c05283e858bc0856681314f2fe79f5bc5eb3ffb451189cbb6c4aee4e643ccd70e60198698eadafd0dcfc7401199e347885c19683d03c1f1b00")
+        );
+        identity.public_key().verify(b"abc", b"foo", &signature)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn verify_rejects() -> crate::Result<()> {
+        let identity = IdentityKey::generate();
+        let public = identity.public_key();
+        let signature = identity.sign(b"Lorem ipsum", b"test");
+
+        public.verify(b"Lorem ipsum", b"test", &signature)?;
+        assert_eq!(
+            public.verify(b"Lorem ipsun", b"test", &signature),
+            Err(MyKeyringError::InvalidSignature)
+        );
+        assert_eq!(
+            public.verify(b"Lorem ipsum", b"other", &signature),
+            Err(MyKeyringError::InvalidSignature)
+        );
+        assert_eq!(
+            IdentityKey::generate()
+                .public_key()
+                .verify(b"Lorem ipsum", b"test", &signature),
+            Err(MyKeyringError::InvalidSignature)
+        );
+
+        for &position in &[0, 60, SIGNATURE_LENGTH - 1] {
+            let mut altered = *signature.as_bytes();
+            altered[position] ^= 1;
+            assert_eq!(
+                public.verify(b"Lorem ipsum", b"test", &Signature(altered)),
+                Err(MyKeyringError::InvalidSignature)
+            );
+        }
+
+        Ok(())
+    }
+}
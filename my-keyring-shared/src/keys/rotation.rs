@@ -0,0 +1,251 @@
+use serde::{Deserialize, Serialize};
+use ulid::Ulid;
+
+use crate::{
+    keys::{Fingerprint, IdentityPublicKey, KeyRing},
+    request::{Signed, SignedPayload},
+    secret::Locked,
+    MyKeyringError, PublicKey, Secret,
+};
+
+/// Length of a [`KeyId`]
+pub const KEY_ID_LENGTH: usize = 8;
+
+/// Identifier of an own x448 key, the beginning of its [`Fingerprint`]
+///
+/// It is stored in the encrypted payloads, so the recipient knows which of its
+/// current or retired keys must be used to decrypt them.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
+pub struct KeyId([u8; KEY_ID_LENGTH]);
+
+impl KeyId {
+    /// Compute the identifier of the `public_key`
+    pub fn new(public_key: &PublicKey) -> Self {
+        let mut id = [0; KEY_ID_LENGTH];
+        id.copy_from_slice(&Fingerprint::new(public_key).as_bytes()[..KEY_ID_LENGTH]);
+        Self(id)
+    }
+
+    /// Return the identifier bytes
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8; KEY_ID_LENGTH] {
+        &self.0
+    }
+}
+
+impl From<[u8; KEY_ID_LENGTH]> for KeyId {
+    fn from(id: [u8; KEY_ID_LENGTH]) -> Self {
+        Self(id)
+    }
+}
+
+/// Announcement of a new own `PublicKey`, sent to the known peers after a
+/// [`KeyRing::rotate`]
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct KeyRotation {
+    /// Identifier of the key replaced
+    pub previous_key_id: KeyId,
+    /// New x448 `PublicKey`
+    pub public_key: Vec<u8>,
+}
+
+impl SignedPayload for KeyRotation {
+    const CONTEXT: &'static [u8] = b"my-keyring KeyRotation";
+}
+
+impl KeyRing {
+    /// Return the identifier of my current key
+    #[inline]
+    pub fn key_id(&self) -> KeyId {
+        self.my_key_id
+    }
+
+    /// Replace my current key by a new random one, keeping the previous one to
+    /// decrypt the payloads already encrypted for it
+    ///
+    /// The announcement returned, signed by my identity, must be sent to the
+    /// known peers, so they encrypt for the new key with
+    /// [`KeyRing::accept_rotation`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> my_keyring_shared::Result<()> {
+    /// use my_keyring_shared::{keys::KeyRing, PublicKey, Secret};
+    /// use rand_core::OsRng;
+    /// use ulid::Ulid;
+    ///
+    /// let phone = Secret::new(&mut OsRng);
+    /// let phone_id = Ulid::new();
+    /// let mut browser = KeyRing::new(Secret::new(&mut OsRng), Default::default());
+    /// browser.add_known(phone_id, PublicKey::from(&phone));
+    ///
+    /// let mut phone = KeyRing::new(phone, Default::default());
+    /// let rotation = phone.rotate();
+    ///
+    /// // On the browser, knowing the phone identity
+    /// browser.accept_rotation(phone_id, &rotation, &phone.identity_public_key())?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn rotate(&mut self) -> Signed<KeyRotation> {
+        let previous_key_id = self.my_key_id;
+        let secret = Secret::new(&mut rand_core::OsRng);
+        let public_key = PublicKey::from(&secret);
+
+        let previous = std::mem::replace(&mut self.my_key, Locked::new(secret));
+        self.retired.insert(previous_key_id, previous);
+        self.my_key_id = KeyId::new(&public_key);
+
+        Signed::new(
+            KeyRotation {
+                previous_key_id,
+                public_key: public_key.as_bytes().to_vec(),
+            },
+            self,
+        )
+    }
+
+    /// List the identifiers of my retired keys, in no particular order
+    pub fn retired_keys(&self) -> impl Iterator<Item = KeyId> + '_ {
+        self.retired.keys().copied()
+    }
+
+    /// Forget the retired key `key_id`, once no payload encrypted for it
+    /// remains, returning if it was present
+    pub fn forget_retired(&mut self, key_id: KeyId) -> bool {
+        self.retired.remove(&key_id).is_some()
+    }
+
+    /// Replace the known key of the peer `known_id` by the one announced in
+    /// the `rotation`, signed by the peer `issuer` identity
+    ///
+    /// # Errors
+    ///
+    /// Return [`MyKeyringError::InvalidSignature`] if the `rotation` has not
+    /// been signed by the `issuer`, [`MyKeyringError::UnknownRecipient`] if
+    /// `known_id` is not in the known pool,
+    /// [`MyKeyringError::StaleKeyRotation`] if the rotation does not replace
+    /// the currently known key, like an older one replayed, and
+    /// [`MyKeyringError::DHError`] if the new key is invalid
+    pub fn accept_rotation(
+        &mut self,
+        known_id: Ulid,
+        rotation: &Signed<KeyRotation>,
+        issuer: &IdentityPublicKey,
+    ) -> crate::Result<()> {
+        let rotation = rotation.verify(issuer)?;
+        let known = self
+            .known
+            .get_mut(&known_id)
+            .ok_or(MyKeyringError::UnknownRecipient)?;
+        if KeyId::new(known) != rotation.previous_key_id {
+            return Err(MyKeyringError::StaleKeyRotation);
+        }
+
+        *known = PublicKey::from_bytes(&rotation.public_key).ok_or(MyKeyringError::DHError)?;
+        Ok(())
+    }
+
+    /// Return my current or retired key `key_id`
+    pub(crate) fn secret(&self, key_id: KeyId) -> crate::Result<&Secret> {
+        if key_id == self.my_key_id {
+            return Ok(&self.my_key);
+        }
+        self.retired
+            .get(&key_id)
+            .map(|secret| &**secret)
+            .ok_or(MyKeyringError::UnknownRecipient)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_core::OsRng;
+
+    use super::*;
+    use crate::keys::seal;
+
+    fn pair() -> (Ulid, KeyRing, KeyRing) {
+        let phone = Secret::new(&mut OsRng);
+        let phone_id = Ulid::new();
+        let mut browser = KeyRing::new(Secret::new(&mut OsRng), Default::default());
+        browser.add_known(phone_id, PublicKey::from(&phone));
+
+        (phone_id, KeyRing::new(phone, Default::default()), browser)
+    }
+
+    #[test]
+    fn rotate_keeps_retired_keys() -> crate::Result<()> {
+        let (phone_id, mut phone, mut browser) = pair();
+        let first_key_id = phone.key_id();
+
+        let before = seal(browser.known.get(&phone_id).unwrap(), b"before")?;
+        let envelope = browser.encrypt_for(&[phone_id], b"envelope", None)?;
+
+        let rotation = phone.rotate();
+        assert_ne!(phone.key_id(), first_key_id);
+        assert_eq!(phone.retired_keys().collect::<Vec<_>>(), [first_key_id]);
+
+        browser.accept_rotation(phone_id, &rotation, &phone.identity_public_key())?;
+        let after = seal(browser.known.get(&phone_id).unwrap(), b"after")?;
+        assert_eq!(
+            KeyId::new(browser.known.get(&phone_id).unwrap()),
+            phone.key_id()
+        );
+
+        // Both the old and the new payloads can be decrypted
+        assert_eq!(phone.open(&before)?, b"before");
+        assert_eq!(phone.open(&after)?, b"after");
+        assert_eq!(
+            phone.decrypt_envelope(phone_id, &envelope, None)?,
+            b"envelope"
+        );
+
+        // Until the retired key is forgotten
+        assert!(phone.forget_retired(first_key_id));
+        assert!(!phone.forget_retired(first_key_id));
+        assert_eq!(
+            phone.open(&before).unwrap_err(),
+            MyKeyringError::UnknownRecipient
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn reject_rotation() {
+        let (phone_id, mut phone, mut browser) = pair();
+        let first = phone.rotate();
+        let second = phone.rotate();
+
+        // Not signed by the phone
+        assert_eq!(
+            browser
+                .accept_rotation(phone_id, &first, &browser.identity_public_key())
+                .unwrap_err(),
+            MyKeyringError::InvalidSignature
+        );
+        // Out of order
+        assert_eq!(
+            browser
+                .accept_rotation(phone_id, &second, &phone.identity_public_key())
+                .unwrap_err(),
+            MyKeyringError::StaleKeyRotation
+        );
+        assert!(browser
+            .accept_rotation(phone_id, &first, &phone.identity_public_key())
+            .is_ok());
+        // Replayed
+        assert_eq!(
+            browser
+                .accept_rotation(phone_id, &first, &phone.identity_public_key())
+                .unwrap_err(),
+            MyKeyringError::StaleKeyRotation
+        );
+        assert!(browser
+            .accept_rotation(phone_id, &second, &phone.identity_public_key())
+            .is_ok());
+    }
+}
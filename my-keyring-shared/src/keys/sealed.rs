@@ -0,0 +1,181 @@
+use core::convert::{TryFrom, TryInto};
+
+use crate::{
+    crypt::{crypt, decrypt, CryptedMessage},
+    keys::{ephemeral_shared, KeyId, KeyRing, KEY_ID_LENGTH},
+    secret::{Locked, SecretBytes},
+    MyKeyringError, PublicKey,
+};
+
+/// Magic number at the beginning of every sealed box
+const MAGIC: &[u8; 4] = b"MKSB";
+/// Current version of the sealed box format
+const VERSION: u8 = 1;
+/// Length of a serialised x448 `PublicKey`
+const PUBLIC_KEY_LENGTH: usize = 56;
+/// Application specific context of the sealed box encryption
+const CONTEXT: &[u8] = b"my-keyring sealed box";
+
+/// Encrypt `data` anonymously for the owner of `recipient_public`
+///
+/// An ephemeral key is generated for each call, and bundled with the
+/// [`CryptedMessage`] into a single blob, that only the recipient can open with
+/// [`KeyRing::open`]. This is the format of
+/// [`PushRequest::encrypted_data`](crate::request::PushRequest::encrypted_data).
+///
+/// # Wire format
+///
+/// | Field     | Length | Content                                        |
+/// |-----------|--------|------------------------------------------------|
+/// | magic     | 4      | `MKSB`                                         |
+/// | version   | 1      | `1`                                            |
+/// | key id    | 8      | [`KeyId`] of `recipient_public`                |
+/// | ephemeral | 56     | ephemeral x448 `PublicKey`                     |
+/// | message   | ..     | serialised [`CryptedMessage`]                  |
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> my_keyring_shared::Result<()> {
+/// use my_keyring_shared::{
+///     keys::{seal, KeyRing},
+///     PublicKey, Secret,
+/// };
+/// use rand_core::OsRng;
+///
+/// let secret = Secret::new(&mut OsRng);
+/// let blob = seal(&PublicKey::from(&secret), b"Lorem ipsum")?;
+///
+/// let keyring = KeyRing::new(secret, Default::default());
+/// assert_eq!(keyring.open(&blob)?, b"Lorem ipsum");
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// Return [`MyKeyringError::DataLengthExceeded`] if `data` is too big to be
+/// encrypted
+pub fn seal(recipient_public: &PublicKey, data: &[u8]) -> crate::Result<Vec<u8>> {
+    let (ephemeral, shared) = ephemeral_shared(recipient_public);
+    let aad = sealed_aad(&ephemeral, recipient_public);
+    let encrypted: Vec<u8> = crypt(&shared, data, Some(CONTEXT), Some(&aad))?.into();
+
+    let mut blob = MAGIC.to_vec();
+    blob.push(VERSION);
+    blob.extend_from_slice(KeyId::new(recipient_public).as_bytes());
+    blob.extend_from_slice(ephemeral.as_bytes());
+    blob.extend(encrypted);
+    Ok(blob)
+}
+
+/// Associated data of a sealed box, binding both the ephemeral and the
+/// recipient keys
+fn sealed_aad(ephemeral: &PublicKey, recipient_public: &PublicKey) -> Vec<u8> {
+    let mut aad = ephemeral.as_bytes().to_vec();
+    aad.extend_from_slice(recipient_public.as_bytes());
+    aad
+}
+
+impl KeyRing {
+    /// Decrypt a `blob` sealed with [`seal`] for this `KeyRing` current or
+    /// retired secret
+    ///
+    /// # Errors
+    ///
+    /// Return [`MyKeyringError::InvalidCryptedMessage`] if `blob` is not a
+    /// sealed box, [`MyKeyringError::UnsupportedCryptedMessageVersion`] if it
+    /// has been written by a newer format, [`MyKeyringError::DHError`] if the
+    /// ephemeral key is invalid, [`MyKeyringError::UnknownRecipient`] if the
+    /// key it has been sealed for is not one of mine, and
+    /// [`MyKeyringError::IncorrectHmac`] if it has not been sealed for this
+    /// secret, or has been altered
+    pub fn open(&self, blob: &[u8]) -> crate::Result<SecretBytes> {
+        if blob.len() < MAGIC.len() + 1 || &blob[..MAGIC.len()] != MAGIC {
+            return Err(MyKeyringError::InvalidCryptedMessage);
+        }
+        if blob[MAGIC.len()] != VERSION {
+            return Err(MyKeyringError::UnsupportedCryptedMessageVersion);
+        }
+        let blob = &blob[MAGIC.len() + 1..];
+        if blob.len() < KEY_ID_LENGTH + PUBLIC_KEY_LENGTH {
+            return Err(MyKeyringError::InvalidCryptedMessage);
+        }
+        let (key_id, blob) = blob.split_at(KEY_ID_LENGTH);
+        let key_id: [u8; KEY_ID_LENGTH] = key_id.try_into().expect("key id length");
+        let key_id = KeyId::from(key_id);
+        if blob.len() < PUBLIC_KEY_LENGTH {
+            return Err(MyKeyringError::InvalidCryptedMessage);
+        }
+
+        let ephemeral =
+            PublicKey::from_bytes(&blob[..PUBLIC_KEY_LENGTH]).ok_or(MyKeyringError::DHError)?;
+        let encrypted = CryptedMessage::try_from(&blob[PUBLIC_KEY_LENGTH..])?;
+
+        let secret = self.secret(key_id)?;
+        let shared = secret
+            .as_diffie_hellman(&ephemeral)
+            .map(Locked::new)
+            .ok_or(MyKeyringError::DHError)?;
+        let aad = sealed_aad(&ephemeral, &PublicKey::from(secret));
+        decrypt(&shared, encrypted, Some(CONTEXT), Some(&aad))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_core::OsRng;
+
+    use super::*;
+    use crate::Secret;
+
+    #[test]
+    fn seal_then_open() -> crate::Result<()> {
+        let secret = Secret::new(&mut OsRng);
+        let public = PublicKey::from(&secret);
+        let keyring = KeyRing::new(secret, Default::default());
+
+        let blob = seal(&public, b"Lorem ipsum")?;
+        assert_eq!(&blob[..5], b"MKSB\x01");
+        assert_eq!(&blob[5..13], keyring.key_id().as_bytes());
+        assert_eq!(keyring.open(&blob)?, b"Lorem ipsum");
+
+        // A new ephemeral key for each box
+        let header = 5 + KEY_ID_LENGTH + PUBLIC_KEY_LENGTH;
+        assert_ne!(&seal(&public, b"Lorem ipsum")?[..header], &blob[..header]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn open_wrong_box() -> crate::Result<()> {
+        let keyring = KeyRing::new(Secret::new(&mut OsRng), Default::default());
+        let other = PublicKey::from(&Secret::new(&mut OsRng));
+
+        let blob = seal(&other, b"Lorem ipsum")?;
+        assert_eq!(
+            keyring.open(&blob).unwrap_err(),
+            MyKeyringError::UnknownRecipient
+        );
+        // Pretending to be for my key
+        let mut altered = blob.clone();
+        altered[5..5 + KEY_ID_LENGTH].copy_from_slice(keyring.key_id().as_bytes());
+        assert_eq!(
+            keyring.open(&altered).unwrap_err(),
+            MyKeyringError::IncorrectHmac
+        );
+
+        assert_eq!(
+            keyring.open(&blob[..20]).unwrap_err(),
+            MyKeyringError::InvalidCryptedMessage
+        );
+        let mut altered = blob;
+        altered[MAGIC.len()] = VERSION + 1;
+        assert_eq!(
+            keyring.open(&altered).unwrap_err(),
+            MyKeyringError::UnsupportedCryptedMessageVersion
+        );
+
+        Ok(())
+    }
+}
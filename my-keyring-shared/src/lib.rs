@@ -79,15 +79,21 @@ pub use x448::{PublicKey, Secret, SharedSecret};
 pub use crate::{algo::Algorithm, errors::MyKeyringError};
 
 mod algo;
-mod authentication;
+pub mod authentication;
 pub mod crypt;
 mod errors;
-mod keys;
-mod note;
+pub mod generator;
+pub mod hotp;
+pub mod item;
+pub mod keys;
+pub mod note;
+pub mod ocra;
 pub mod request;
+pub mod secret;
 pub mod security;
-mod tag;
+pub mod tag;
 pub mod totp;
+pub mod vault;
 
 /// Specialized [`core::result::Result`] for this crate
 pub type Result<T> = core::result::Result<T, MyKeyringError>;
@@ -1,22 +1,76 @@
+//! Contains the `Note`, a free text entry
+
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
 use ulid::Ulid;
 
-use crate::tag::Tags;
+use crate::{authentication::ulid_time, tag::Tags};
 
-#[derive(Debug)]
+/// Free text
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Note {
+    /// Identifier, also giving the creation time
     id: Ulid,
+    /// Generated at each modification, giving its time
+    modified: Ulid,
     message: String,
     tags: Vec<Ulid>,
 }
 
 impl Note {
+    /// Create a note holding the `message`
     pub fn new(message: &str) -> Self {
+        let id = Ulid::new();
+
         Self {
-            id: Ulid::new(),
+            id,
+            modified: id,
             message: message.to_owned(),
             tags: Vec::new(),
         }
     }
+
+    /// Return the identifier
+    #[inline]
+    pub fn id(&self) -> Ulid {
+        self.id
+    }
+
+    /// Return the creation time, taken from the identifier
+    #[inline]
+    pub fn created(&self) -> SystemTime {
+        ulid_time(self.id)
+    }
+
+    /// Return the time of the last modification
+    #[inline]
+    pub fn modified(&self) -> SystemTime {
+        ulid_time(self.modified)
+    }
+
+    /// Return the text
+    #[inline]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Replace the text
+    pub fn set_message(&mut self, message: &str) {
+        self.message = message.to_owned();
+        self.touch();
+    }
+
+    /// Return the identifiers of the tags, see [`crate::tag::TagPool`]
+    #[inline]
+    pub fn tag_ids(&self) -> &[Ulid] {
+        &self.tags
+    }
+
+    /// Record a modification, now
+    fn touch(&mut self) {
+        self.modified = Ulid::new();
+    }
 }
 
 impl Tags for Note {
@@ -24,4 +78,32 @@ impl Tags for Note {
     fn tags(&mut self) -> &mut Vec<Ulid> {
         &mut self.tags
     }
+
+    #[inline]
+    fn tags_changed(&mut self) {
+        self.touch();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modifications() {
+        let mut note = Note::new("Lorem ipsum");
+        assert_eq!(note.created(), note.modified());
+        let modified = note.modified;
+
+        note.set_message("Dolor sit amet");
+        assert_eq!(note.message(), "Dolor sit amet");
+        assert_ne!(note.modified, modified);
+
+        // Removing a missing tag changes nothing
+        let modified = note.modified;
+        note.del_tag(Ulid::new());
+        assert_eq!(note.modified, modified);
+        note.add_tag(Ulid::new());
+        assert_ne!(note.modified, modified);
+    }
 }
@@ -0,0 +1,742 @@
+//! Contains the code to compute OCRA challenge-response, as defined in RFC 6287
+//!
+//! The suite, such as `OCRA-1:HOTP-SHA256-8:QN08-T1M`, describes the hmac
+//! algorithm, the response length and the data that are mixed in the
+//! computation: an optional counter, the challenge, an optional PIN hash, an
+//! optional session information and an optional timestamp.
+//!
+//! # Examples
+//!
+//! ```
+//! # fn main() -> my_keyring_shared::Result<()> {
+//! use my_keyring_shared::ocra::Ocra;
+//! // 12345678901234567890
+//! let ocra = Ocra::new(
+//!     "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ",
+//!     "OCRA-1:HOTP-SHA1-6:QN08",
+//! )?;
+//!
+//! assert_eq!("243178", ocra.response("11111111", None, None, None, None)?);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{convert::TryFrom, str::FromStr, time::SystemTime};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    algo::Algorithm,
+    errors::MyKeyringError,
+    secret::{SecretBytes, SecretString},
+    totp::{decimal_code, decode_base32, dynamic_truncation},
+};
+
+/// Length of the challenge once padded, in bytes
+const QUESTION_LENGTH: usize = 128;
+
+/// Format of the challenge
+#[derive(Debug, PartialEq, Copy, Clone, Serialize, Deserialize)]
+pub enum QuestionFormat {
+    /// Alphanumeric characters, `QA`
+    Alphanumeric,
+    /// Decimal digits, `QN`
+    Numeric,
+    /// Hexadecimal digits, `QH`
+    Hexadecimal,
+}
+
+/// Parsed OCRA suite
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> my_keyring_shared::Result<()> {
+/// use my_keyring_shared::{ocra::OcraSuite, Algorithm};
+/// let suite: OcraSuite = "OCRA-1:HOTP-SHA512-8:C-QN08-PSHA1".parse()?;
+///
+/// assert_eq!(Algorithm::Sha512, suite.algorithm());
+/// assert_eq!(8, suite.digits());
+/// assert!(suite.has_counter());
+/// # Ok(())
+/// # }
+/// ```
+///
+/// It is serialised as its suite string, parsed again when read.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct OcraSuite {
+    /// Original suite string, part of the computed message
+    suite: String,
+    /// Algorithm of the hmac
+    algorithm: Algorithm,
+    /// Number of digits of the response, 0 meaning no truncation
+    digits: u8,
+    /// A counter is part of the data input
+    counter: bool,
+    /// Format and maximum length of the challenge
+    question: (QuestionFormat, u8),
+    /// Algorithm of the PIN hash, if part of the data input
+    pin: Option<Algorithm>,
+    /// Length of the session information in bytes, if part of the data input
+    session: Option<u16>,
+    /// Length of the time step in seconds, if part of the data input
+    time_step: Option<u64>,
+}
+
+impl OcraSuite {
+    /// Return the algorithm of the hmac
+    #[inline]
+    pub fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    /// Return the number of digits of the response, 0 meaning the complete
+    /// hmac is returned, hexadecimal encoded
+    #[inline]
+    pub fn digits(&self) -> u8 {
+        self.digits
+    }
+
+    /// Return `true` if a counter is needed to compute the response
+    #[inline]
+    pub fn has_counter(&self) -> bool {
+        self.counter
+    }
+
+    /// Return the format and the maximum length of the challenge
+    #[inline]
+    pub fn question(&self) -> (QuestionFormat, u8) {
+        self.question
+    }
+
+    /// Return the algorithm of the PIN hash, if needed to compute the response
+    #[inline]
+    pub fn pin(&self) -> Option<Algorithm> {
+        self.pin
+    }
+
+    /// Return the length of the session information, if needed to compute the
+    /// response
+    #[inline]
+    pub fn session(&self) -> Option<u16> {
+        self.session
+    }
+
+    /// Return the time step in seconds, if a timestamp is needed to compute
+    /// the response
+    #[inline]
+    pub fn time_step(&self) -> Option<u64> {
+        self.time_step
+    }
+}
+
+impl FromStr for OcraSuite {
+    type Err = MyKeyringError;
+
+    fn from_str(suite: &str) -> Result<Self, Self::Err> {
+        // Only ASCII, so that the parts can be split at any byte
+        if !suite.is_ascii() {
+            return Err(MyKeyringError::InvalidOcraSuite);
+        }
+
+        let mut parts = suite.split(':');
+        let (version, crypto, data_input) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(version), Some(crypto), Some(data_input)) if parts.next().is_none() => {
+                (version, crypto, data_input)
+            }
+            _ => return Err(MyKeyringError::InvalidOcraSuite),
+        };
+        if version != "OCRA-1" {
+            return Err(MyKeyringError::InvalidOcraSuite);
+        }
+
+        // Crypto function, HOTP-SHAx-t
+        let mut crypto = crypto.split('-');
+        let (algorithm, digits) = match (crypto.next(), crypto.next(), crypto.next()) {
+            (Some("HOTP"), Some(algorithm), Some(digits)) if crypto.next().is_none() => {
+                (parse_algorithm(algorithm)?, digits)
+            }
+            _ => return Err(MyKeyringError::InvalidOcraSuite),
+        };
+        let digits = parse_number::<u8>(digits)
+            .filter(|d| *d == 0 || (4..=10).contains(d))
+            .ok_or(MyKeyringError::InvalidOcraSuite)?;
+
+        // Data input, [C][-QFxx][-PH][-Snnn][-TG], in this order
+        let mut ocra_suite = Self {
+            suite: suite.to_owned(),
+            algorithm,
+            digits,
+            counter: false,
+            question: (QuestionFormat::Numeric, 0),
+            pin: None,
+            session: None,
+            time_step: None,
+        };
+        let mut position = 0;
+        for input in data_input.split('-') {
+            let (order, value) = input.split_at(input.len().min(1));
+            let order = match order {
+                "C" if value.is_empty() => {
+                    ocra_suite.counter = true;
+                    1
+                }
+                "Q" => {
+                    ocra_suite.question = parse_question(value)?;
+                    2
+                }
+                "P" => {
+                    ocra_suite.pin = Some(parse_algorithm(value)?);
+                    3
+                }
+                "S" if value.len() == 3 => {
+                    ocra_suite.session = Some(
+                        parse_number(value)
+                            .filter(|s| *s > 0)
+                            .ok_or(MyKeyringError::InvalidOcraSuite)?,
+                    );
+                    4
+                }
+                "T" => {
+                    ocra_suite.time_step = Some(parse_time_step(value)?);
+                    5
+                }
+                _ => return Err(MyKeyringError::InvalidOcraSuite),
+            };
+            // Each input appears once, in the order of the message
+            if order <= position {
+                return Err(MyKeyringError::InvalidOcraSuite);
+            }
+            position = order;
+        }
+        // The challenge is mandatory
+        if ocra_suite.question.1 == 0 {
+            return Err(MyKeyringError::InvalidOcraSuite);
+        }
+
+        Ok(ocra_suite)
+    }
+}
+
+/// Compute OCRA responses, based on a secret and a suite
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Ocra {
+    /// Secret to use
+    secret: SecretString,
+    /// Suite describing the computation
+    suite: OcraSuite,
+}
+
+impl TryFrom<String> for OcraSuite {
+    type Error = &'static str;
+
+    fn try_from(suite: String) -> Result<Self, Self::Error> {
+        suite.parse().map_err(|_| "invalid OCRA suite")
+    }
+}
+
+impl From<OcraSuite> for String {
+    fn from(suite: OcraSuite) -> Self {
+        suite.suite
+    }
+}
+
+impl Ocra {
+    /// Initialise a new Ocra, from the base32 `secret` and the `suite` string
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use my_keyring_shared::ocra::Ocra;
+    /// # fn main() -> my_keyring_shared::Result<()> {
+    /// let ocra = Ocra::new("JBSWY3DPEB3W64TMMQQQ", "OCRA-1:HOTP-SHA256-8:QN08-T1M")?;
+    ///
+    /// assert_eq!(Some(60), ocra.suite().time_step());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Return [`MyKeyringError::InvalidBase32`] error if the provided `secret`
+    /// is not a valid base32 encoded string, or
+    /// [`MyKeyringError::InvalidOcraSuite`] if the `suite` cannot be parsed
+    pub fn new(secret: &str, suite: &str) -> crate::Result<Self> {
+        // Do this to check base32 string
+        let _ = decode_base32(secret)?;
+
+        Ok(Self {
+            secret: secret.into(),
+            suite: suite.parse()?,
+        })
+    }
+
+    /// Return the suite used to compute the responses
+    #[inline]
+    pub fn suite(&self) -> &OcraSuite {
+        &self.suite
+    }
+
+    /// Compute the response to the `challenge`
+    ///
+    /// For a mutual challenge-response, the `challenge` is the concatenation of
+    /// the client and the server ones, so it can be longer than the maximum
+    /// length of the suite, as long as it fits in the 128 bytes of the message.
+    /// The `counter`, `pin_hash` and `session` must be provided if the suite
+    /// needs them, and are ignored otherwise. The `timestamp`, in seconds since
+    /// the Unix epoch, defaults to the current time when the suite needs one.
+    /// A `session` shorter than the suite length is padded with leading zeros.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> my_keyring_shared::Result<()> {
+    /// use my_keyring_shared::{ocra::Ocra, Algorithm};
+    /// // 12345678901234567890123456789012
+    /// let ocra = Ocra::new(
+    ///     "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQGEZA",
+    ///     "OCRA-1:HOTP-SHA256-8:C-QN08-PSHA1",
+    /// )?;
+    /// // SHA-1 of the "1234" PIN
+    /// let pin_hash = Algorithm::Sha1.hash(b"1234");
+    ///
+    /// assert_eq!(
+    ///     "65347737",
+    ///     ocra.response("12345678", 0, Some(&pin_hash), None, None)?
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Return [`MyKeyringError::InvalidOcraChallenge`] if the `challenge` is
+    /// empty, too long or does not match the suite format, and
+    /// [`MyKeyringError::InvalidOcraInput`] if an input needed by the suite is
+    /// missing or has an invalid length
+    pub fn response(
+        &self,
+        challenge: &str,
+        counter: impl Into<Option<u64>>,
+        pin_hash: Option<&[u8]>,
+        session: Option<&[u8]>,
+        timestamp: impl Into<Option<u64>>,
+    ) -> crate::Result<String> {
+        let suite = &self.suite;
+
+        // Suite, then a 0 byte separator
+        let mut message = suite.suite.as_bytes().to_vec();
+        message.push(0);
+
+        // Counter, 8 bytes
+        if suite.counter {
+            let counter = counter.into().ok_or(MyKeyringError::InvalidOcraInput)?;
+            message.extend_from_slice(&counter.to_be_bytes());
+        }
+
+        // Challenge, 128 bytes
+        message.extend_from_slice(&encode_question(challenge, suite.question.0)?);
+
+        // PIN hash
+        if let Some(algorithm) = suite.pin {
+            match pin_hash {
+                Some(pin_hash) if pin_hash.len() == algorithm.digest_length() => {
+                    message.extend_from_slice(pin_hash)
+                }
+                _ => return Err(MyKeyringError::InvalidOcraInput),
+            }
+        }
+
+        // Session information, left padded to the suite length
+        if let Some(length) = suite.session {
+            match session {
+                Some(session) if session.len() <= usize::from(length) => {
+                    message.resize(message.len() + usize::from(length) - session.len(), 0);
+                    message.extend_from_slice(session);
+                }
+                _ => return Err(MyKeyringError::InvalidOcraInput),
+            }
+        }
+
+        // Timestamp, 8 bytes
+        if let Some(time_step) = suite.time_step {
+            let timestamp = timestamp.into().unwrap_or_else(|| {
+                SystemTime::now()
+                    .duration_since(SystemTime::UNIX_EPOCH)
+                    .unwrap()
+                    .as_secs()
+            });
+            message.extend_from_slice(&(timestamp / time_step).to_be_bytes());
+        }
+
+        // Compute the Hmac
+        let digest = suite.algorithm.hmac(
+            &SecretBytes::from(decode_base32(&self.secret).expect("Base32 decoded string")),
+            &message,
+        );
+
+        // Truncate
+        Ok(if suite.digits == 0 {
+            digest.iter().map(|b| format!("{:02x}", b)).collect()
+        } else {
+            decimal_code(dynamic_truncation(&digest), suite.digits)
+        })
+    }
+}
+
+/// Parse the hash algorithm name, as used in the suite
+fn parse_algorithm(value: &str) -> crate::Result<Algorithm> {
+    match value {
+        "SHA1" => Ok(Algorithm::Sha1),
+        "SHA256" => Ok(Algorithm::Sha256),
+        "SHA512" => Ok(Algorithm::Sha512),
+        _ => Err(MyKeyringError::InvalidOcraSuite),
+    }
+}
+
+/// Parse a decimal number, of digits only
+fn parse_number<T: FromStr>(value: &str) -> Option<T> {
+    if value.is_empty() || !value.bytes().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    value.parse().ok()
+}
+
+/// Parse the `Fxx` part of the challenge description
+fn parse_question(value: &str) -> crate::Result<(QuestionFormat, u8)> {
+    if value.len() != 3 {
+        return Err(MyKeyringError::InvalidOcraSuite);
+    }
+    let (format, length) = value.split_at(1);
+    let format = match format {
+        "A" => QuestionFormat::Alphanumeric,
+        "N" => QuestionFormat::Numeric,
+        "H" => QuestionFormat::Hexadecimal,
+        _ => return Err(MyKeyringError::InvalidOcraSuite),
+    };
+    let length = parse_number::<u8>(length)
+        .filter(|l| (4..=64).contains(l))
+        .ok_or(MyKeyringError::InvalidOcraSuite)?;
+
+    Ok((format, length))
+}
+
+/// Parse the `G` part of the timestamp description, returning the time step in
+/// seconds
+fn parse_time_step(value: &str) -> crate::Result<u64> {
+    if value.len() < 2 {
+        return Err(MyKeyringError::InvalidOcraSuite);
+    }
+    let (number, unit) = value.split_at(value.len() - 1);
+    let number = parse_number::<u64>(number).ok_or(MyKeyringError::InvalidOcraSuite)?;
+    let (max, seconds) = match unit {
+        "S" => (59, 1),
+        "M" => (59, 60),
+        "H" => (48, 60 * 60),
+        _ => return Err(MyKeyringError::InvalidOcraSuite),
+    };
+    if number == 0 || number > max {
+        return Err(MyKeyringError::InvalidOcraSuite);
+    }
+
+    Ok(number * seconds)
+}
+
+/// Convert the `challenge` into the 128 bytes of the message
+///
+/// The challenge is first converted to an hexadecimal string, then right
+/// padded with `0` and decoded, as done by the reference implementation.
+fn encode_question(
+    challenge: &str,
+    format: QuestionFormat,
+) -> crate::Result<[u8; QUESTION_LENGTH]> {
+    if challenge.is_empty() {
+        return Err(MyKeyringError::InvalidOcraChallenge);
+    }
+
+    let hex = match format {
+        QuestionFormat::Alphanumeric if challenge.bytes().all(|c| c.is_ascii_graphic()) => {
+            challenge.bytes().map(|b| format!("{:02X}", b)).collect()
+        }
+        QuestionFormat::Numeric if challenge.bytes().all(|c| c.is_ascii_digit()) => {
+            decimal_to_hex(challenge)
+        }
+        QuestionFormat::Hexadecimal if challenge.bytes().all(|c| c.is_ascii_hexdigit()) => {
+            challenge.to_owned()
+        }
+        _ => return Err(MyKeyringError::InvalidOcraChallenge),
+    };
+    if hex.len() > QUESTION_LENGTH * 2 {
+        return Err(MyKeyringError::InvalidOcraChallenge);
+    }
+
+    let mut question = [0; QUESTION_LENGTH];
+    for (i, digit) in hex.bytes().enumerate() {
+        let nibble = char::from(digit).to_digit(16).expect("hexadecimal digit") as u8;
+        question[i / 2] |= if i % 2 == 0 { nibble << 4 } else { nibble };
+    }
+
+    Ok(question)
+}
+
+/// Convert a decimal string, of any length, into its hexadecimal
+/// representation without leading zeros
+fn decimal_to_hex(decimal: &str) -> String {
+    // Big endian bytes of the number
+    let mut number: Vec<u8> = Vec::new();
+    for digit in decimal.bytes() {
+        let mut carry = u16::from(digit - b'0');
+        for byte in number.iter_mut().rev() {
+            let value = u16::from(*byte) * 10 + carry;
+            *byte = value as u8;
+            carry = value >> 8;
+        }
+        if carry > 0 {
+            number.insert(0, carry as u8);
+        }
+    }
+
+    let hex: String = number.iter().map(|b| format!("{:02X}", b)).collect();
+    match hex.trim_start_matches('0') {
+        "" => "0".to_owned(),
+        hex => hex.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 12345678901234567890
+    const SEED: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+    // 12345678901234567890123456789012
+    const SEED_32: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQGEZA";
+    // 1234567890123456789012345678901234567890123456789012345678901234
+    const SEED_64: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQGEZDGNA";
+    // 0x132d0b6 minutes
+    const TIMESTAMP: u64 = 20_107_446 * 60;
+
+    fn pin_hash() -> Vec<u8> {
+        Algorithm::Sha1.hash(b"1234")
+    }
+
+    #[test]
+    fn suite_parsing() -> crate::Result<()> {
+        let suite: OcraSuite = "OCRA-1:HOTP-SHA256-6:C-QA64-PSHA512-S128-T2H".parse()?;
+        assert_eq!(suite.algorithm(), Algorithm::Sha256);
+        assert_eq!(suite.digits(), 6);
+        assert!(suite.has_counter());
+        assert_eq!(suite.question(), (QuestionFormat::Alphanumeric, 64));
+        assert_eq!(suite.pin(), Some(Algorithm::Sha512));
+        assert_eq!(suite.session(), Some(128));
+        assert_eq!(suite.time_step(), Some(7200));
+
+        let suite: OcraSuite = "OCRA-1:HOTP-SHA1-0:QH40".parse()?;
+        assert_eq!(suite.digits(), 0);
+        assert!(!suite.has_counter());
+        assert_eq!(suite.pin(), None);
+        assert_eq!(suite.session(), None);
+        assert_eq!(suite.time_step(), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_suites() {
+        for suite in &[
+            "",
+            "OCRA-1:HOTP-SHA1-6",
+            "OCRA-2:HOTP-SHA1-6:QN08",
+            "OCRA-1:TOTP-SHA1-6:QN08",
+            "OCRA-1:HOTP-MD5-6:QN08",
+            "OCRA-1:HOTP-SHA1-3:QN08",
+            "OCRA-1:HOTP-SHA1-11:QN08",
+            "OCRA-1:HOTP-SHA1-6:C",
+            "OCRA-1:HOTP-SHA1-6:QN03",
+            "OCRA-1:HOTP-SHA1-6:QN65",
+            "OCRA-1:HOTP-SHA1-6:QX08",
+            "OCRA-1:HOTP-SHA1-6:QN08-C",
+            "OCRA-1:HOTP-SHA1-6:QN08-QN08",
+            "OCRA-1:HOTP-SHA1-6:QN08-S64",
+            "OCRA-1:HOTP-SHA1-6:QN08-T60S",
+            "OCRA-1:HOTP-SHA1-6:QN08-T0H",
+            "OCRA-1:HOTP-SHA1-6:QN08-T1D",
+            "OCRA-1:HOTP-SHA1-6:QN08:",
+            "OCRA-1:HOTP-SHA1-6:é",
+            "OCRA-1:HOTP-SHA1-6:Qé1",
+            "OCRA-1:HOTP-SHA1-6:QN08-T1é",
+            "OCRA-1:HOTP-SHA1-+6:QN08",
+            "OCRA-1:HOTP-SHA1-6:QN+8",
+            "OCRA-1:HOTP-SHA1-6:QN08-S+64",
+            "OCRA-1:HOTP-SHA1-6:QN08-T+1M",
+        ] {
+            assert_eq!(
+                suite.parse::<OcraSuite>().unwrap_err(),
+                MyKeyringError::InvalidOcraSuite,
+                "{}",
+                suite
+            );
+        }
+    }
+
+    #[test]
+    fn suite_serialization() -> crate::Result<()> {
+        let o = Ocra::new(SEED, "OCRA-1:HOTP-SHA1-6:QN08-T1M")?;
+        let read: Ocra = bincode::deserialize(&bincode::serialize(&o).unwrap()).unwrap();
+        assert_eq!(read.suite().time_step(), Some(60));
+        assert_eq!(
+            read.response("11111111", None, None, None, TIMESTAMP)?,
+            o.response("11111111", None, None, None, TIMESTAMP)?
+        );
+
+        // Checked when read too
+        let read = |suite: &str| -> Option<OcraSuite> {
+            bincode::deserialize(&bincode::serialize(suite).unwrap()).ok()
+        };
+        assert!(read("OCRA-1:HOTP-SHA1-6:QN08-T0S").is_none());
+        assert!(read("OCRA-1:HOTP-SHA1-6:Qé1").is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn tests_vectors_rfc6287_one_way() -> crate::Result<()> {
+        let o = Ocra::new(SEED, "OCRA-1:HOTP-SHA1-6:QN08")?;
+        assert_eq!(o.response("00000000", None, None, None, None)?, "237653");
+        assert_eq!(o.response("11111111", None, None, None, None)?, "243178");
+        assert_eq!(o.response("22222222", None, None, None, None)?, "653583");
+
+        let o = Ocra::new(SEED_32, "OCRA-1:HOTP-SHA256-8:C-QN08-PSHA1")?;
+        let pin = pin_hash();
+        assert_eq!(
+            o.response("12345678", 0, Some(&pin), None, None)?,
+            "65347737"
+        );
+        assert_eq!(
+            o.response("12345678", 1, Some(&pin), None, None)?,
+            "86775851"
+        );
+        assert_eq!(
+            o.response("12345678", 2, Some(&pin), None, None)?,
+            "78192410"
+        );
+
+        let o = Ocra::new(SEED_32, "OCRA-1:HOTP-SHA256-8:QN08-PSHA1")?;
+        assert_eq!(
+            o.response("00000000", None, Some(&pin), None, None)?,
+            "83238735"
+        );
+        assert_eq!(
+            o.response("11111111", None, Some(&pin), None, None)?,
+            "01501458"
+        );
+
+        let o = Ocra::new(SEED_64, "OCRA-1:HOTP-SHA512-8:C-QN08")?;
+        assert_eq!(o.response("00000000", 0, None, None, None)?, "07016083");
+
+        let o = Ocra::new(SEED_64, "OCRA-1:HOTP-SHA512-8:QN08-T1M")?;
+        assert_eq!(
+            o.response("00000000", None, None, None, TIMESTAMP)?,
+            "95209754"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn tests_vectors_rfc6287_mutual_and_signature() -> crate::Result<()> {
+        // Client and server challenges concatenated
+        let o = Ocra::new(SEED_32, "OCRA-1:HOTP-SHA256-8:QA08")?;
+        assert_eq!(
+            o.response("CLI22220SRV11110", None, None, None, None)?,
+            "28247970"
+        );
+        assert_eq!(o.response("SIG10000", None, None, None, None)?, "53095496");
+
+        let o = Ocra::new(SEED_64, "OCRA-1:HOTP-SHA512-8:QA10-T1M")?;
+        assert_eq!(
+            o.response("SIG1000000", None, None, None, TIMESTAMP)?,
+            "77537423"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn session_and_hexadecimal() -> crate::Result<()> {
+        // Values computed with the RFC reference implementation
+        let o = Ocra::new(SEED, "OCRA-1:HOTP-SHA1-6:QN08-S064")?;
+        assert_eq!(
+            o.response("12345678", None, None, Some(&[1, 2]), None)?,
+            "700037"
+        );
+
+        let o = Ocra::new(SEED_32, "OCRA-1:HOTP-SHA256-6:QH08")?;
+        assert_eq!(o.response("ABCDEF1", None, None, None, None)?, "440992");
+
+        Ok(())
+    }
+
+    #[test]
+    fn missing_inputs() -> crate::Result<()> {
+        let o = Ocra::new(SEED_32, "OCRA-1:HOTP-SHA256-8:C-QN08-PSHA1-S004")?;
+        let pin = pin_hash();
+        assert_eq!(
+            o.response("1", None, Some(&pin), Some(b""), None),
+            Err(MyKeyringError::InvalidOcraInput)
+        );
+        assert_eq!(
+            o.response("1", 0, None, Some(b""), None),
+            Err(MyKeyringError::InvalidOcraInput)
+        );
+        assert_eq!(
+            o.response("1", 0, Some(&pin[1..]), Some(b""), None),
+            Err(MyKeyringError::InvalidOcraInput)
+        );
+        assert_eq!(
+            o.response("1", 0, Some(&pin), None, None),
+            Err(MyKeyringError::InvalidOcraInput)
+        );
+        assert_eq!(
+            o.response("1", 0, Some(&pin), Some(b"12345"), None),
+            Err(MyKeyringError::InvalidOcraInput)
+        );
+        assert!(o.response("1", 0, Some(&pin), Some(b"1234"), None).is_ok());
+
+        assert_eq!(
+            o.response("", 0, Some(&pin), Some(b""), None),
+            Err(MyKeyringError::InvalidOcraChallenge)
+        );
+        assert_eq!(
+            o.response(&"1".repeat(400), 0, Some(&pin), Some(b""), None),
+            Err(MyKeyringError::InvalidOcraChallenge)
+        );
+        assert_eq!(
+            o.response("1234567A", 0, Some(&pin), Some(b""), None),
+            Err(MyKeyringError::InvalidOcraChallenge)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn no_truncation() -> crate::Result<()> {
+        let o = Ocra::new(SEED, "OCRA-1:HOTP-SHA1-0:QN08")?;
+        let response = o.response("00000000", None, None, None, None)?;
+        assert_eq!(response.len(), 40);
+        assert!(response.bytes().all(|c| c.is_ascii_hexdigit()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn decimal_conversion() {
+        assert_eq!(decimal_to_hex("0"), "0");
+        assert_eq!(decimal_to_hex("00000000"), "0");
+        assert_eq!(decimal_to_hex("11111111"), "A98AC7");
+        assert_eq!(decimal_to_hex("256"), "100");
+        assert_eq!(decimal_to_hex("18446744073709551616"), "10000000000000000");
+    }
+}
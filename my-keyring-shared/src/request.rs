@@ -1,6 +1,10 @@
+use core::convert::TryFrom;
+
 use serde::{Deserialize, Serialize};
 use ulid::Ulid;
 
+use crate::keys::{IdentityPublicKey, KeyRing, Signature};
+
 #[derive(Deserialize, Serialize, Debug, Clone, Copy)]
 #[serde(deny_unknown_fields, rename_all = "camelCase")]
 pub struct RequestId {
@@ -22,6 +26,149 @@ pub struct ResponseId {
 pub struct PushRequest {
     /// Push token
     pub push_id: String,
-    /// Data to send to the remote, generally a mobile
+    /// Data to send to the remote, generally a mobile, sealed for it with
+    /// [`seal`](crate::keys::seal)
     pub encrypted_data: Option<Vec<u8>>,
 }
+
+/// Payload that can be signed by the identity of its issuer, in a [`Signed`]
+pub trait SignedPayload: Serialize {
+    /// Application specific context of the signature, distinct for each type so
+    /// a signature cannot be reused for another one
+    const CONTEXT: &'static [u8];
+}
+
+impl SignedPayload for PushRequest {
+    const CONTEXT: &'static [u8] = b"my-keyring PushRequest";
+}
+
+impl SignedPayload for ResponseId {
+    const CONTEXT: &'static [u8] = b"my-keyring ResponseId";
+}
+
+/// `payload` signed by the identity of its issuer, so the server and the
+/// devices can check who issued it
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> my_keyring_shared::Result<()> {
+/// use my_keyring_shared::{
+///     keys::KeyRing,
+///     request::{PushRequest, Signed},
+///     Secret,
+/// };
+/// use rand_core::OsRng;
+///
+/// let keyring = KeyRing::new(Secret::new(&mut OsRng), Default::default());
+/// let request = Signed::new(
+///     PushRequest {
+///         push_id: "token".to_owned(),
+///         encrypted_data: None,
+///     },
+///     &keyring,
+/// );
+///
+/// // On the receiving side, knowing the issuer identity
+/// let push = request.verify(&keyring.identity_public_key())?;
+/// assert_eq!(push.push_id, "token");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct Signed<T> {
+    /// Signed data
+    pub payload: T,
+    /// Ed448 signature of the bincode serialised `payload`
+    pub signature: Vec<u8>,
+}
+
+impl<T: SignedPayload> Signed<T> {
+    /// Sign the `payload` with the identity of the `keyring`
+    pub fn new(payload: T, keyring: &KeyRing) -> Self {
+        let signature = keyring.sign(&signed_data(&payload), T::CONTEXT);
+
+        Self {
+            payload,
+            signature: signature.as_bytes().to_vec(),
+        }
+    }
+
+    /// Check that the payload has been signed by the `issuer`, and return it
+    ///
+    /// # Errors
+    ///
+    /// Return [`MyKeyringError::InvalidSignature`] if the payload has not been
+    /// signed by the `issuer`, or has been altered
+    pub fn verify(&self, issuer: &IdentityPublicKey) -> crate::Result<&T> {
+        let signature = Signature::try_from(&self.signature[..])?;
+        issuer.verify(&signed_data(&self.payload), T::CONTEXT, &signature)?;
+
+        Ok(&self.payload)
+    }
+}
+
+/// Data effectively signed for a `payload`, independent of the transport
+/// serialisation
+fn signed_data(payload: &impl Serialize) -> Vec<u8> {
+    bincode::serialize(payload).expect("serialized payload")
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_core::OsRng;
+
+    use super::*;
+    use crate::{MyKeyringError, Secret};
+
+    fn new_keyring() -> KeyRing {
+        KeyRing::new(Secret::new(&mut OsRng), Default::default())
+    }
+
+    #[test]
+    fn signed_push_request() -> crate::Result<()> {
+        let keyring = new_keyring();
+        let push = PushRequest {
+            push_id: "token".to_owned(),
+            encrypted_data: Some(vec![1, 2, 3]),
+        };
+
+        let mut signed = Signed::new(push, &keyring);
+        assert_eq!(
+            signed.verify(&keyring.identity_public_key())?.push_id,
+            "token"
+        );
+        assert_eq!(
+            signed
+                .verify(&new_keyring().identity_public_key())
+                .unwrap_err(),
+            MyKeyringError::InvalidSignature
+        );
+
+        signed.payload.encrypted_data = None;
+        assert_eq!(
+            signed.verify(&keyring.identity_public_key()).unwrap_err(),
+            MyKeyringError::InvalidSignature
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn signed_response_id() -> crate::Result<()> {
+        let keyring = new_keyring();
+        let client_id = Ulid::new();
+
+        let signed = Signed::new(ResponseId { client_id }, &keyring);
+        // Through the transport serialisation
+        let signed: Signed<ResponseId> =
+            bincode::deserialize(&bincode::serialize(&signed).unwrap()).unwrap();
+        assert_eq!(
+            signed.verify(&keyring.identity_public_key())?.client_id,
+            client_id
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,226 @@
+//! Contains the containers of sensitive data, wiped from memory when dropped
+//!
+//! The key material is moreover kept out of the swap by [`Locked`].
+//!
+//! Their `Debug` implementation never prints the content, so they can be
+//! logged safely along with the structures holding them.
+//!
+//! # Examples
+//!
+//! ```
+//! use my_keyring_shared::secret::SecretString;
+//!
+//! let password = SecretString::from("My_secr3tP@55w0rd");
+//!
+//! assert_eq!(&*password, "My_secr3tP@55w0rd");
+//! assert_eq!(format!("{:?}", password), "SecretString([REDACTED])");
+//! ```
+
+use core::{fmt, ops::Deref};
+
+use serde::{Deserialize, Serialize};
+use subtle::ConstantTimeEq;
+use zeroize::Zeroize;
+
+pub use self::locked::Locked;
+
+mod locked;
+
+/// A `String` wiped from memory when dropped
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SecretString(String);
+
+impl SecretString {
+    /// Wrap the `secret`, taking ownership of its buffer
+    #[inline]
+    pub fn new(secret: String) -> Self {
+        Self(secret)
+    }
+
+    /// Return the secret content
+    #[inline]
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Drop for SecretString {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretString([REDACTED])")
+    }
+}
+
+impl Deref for SecretString {
+    type Target = str;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl AsRef<str> for SecretString {
+    #[inline]
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for SecretString {
+    #[inline]
+    fn from(secret: String) -> Self {
+        Self(secret)
+    }
+}
+
+impl From<&str> for SecretString {
+    #[inline]
+    fn from(secret: &str) -> Self {
+        Self(secret.to_owned())
+    }
+}
+
+/// Compared in constant time
+impl PartialEq for SecretString {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.as_bytes().ct_eq(other.0.as_bytes()).into()
+    }
+}
+
+impl Eq for SecretString {}
+
+/// A byte buffer wiped from memory when dropped, like the decrypted data
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SecretBytes(Vec<u8>);
+
+impl SecretBytes {
+    /// Wrap the `secret`, taking ownership of its buffer
+    #[inline]
+    pub fn new(secret: Vec<u8>) -> Self {
+        Self(secret)
+    }
+
+    /// Return the secret content
+    #[inline]
+    pub fn expose(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Drop for SecretBytes {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl fmt::Debug for SecretBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretBytes([REDACTED])")
+    }
+}
+
+impl Deref for SecretBytes {
+    type Target = [u8];
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl AsRef<[u8]> for SecretBytes {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<Vec<u8>> for SecretBytes {
+    #[inline]
+    fn from(secret: Vec<u8>) -> Self {
+        Self(secret)
+    }
+}
+
+/// Compared in constant time
+impl PartialEq<[u8]> for SecretBytes {
+    fn eq(&self, other: &[u8]) -> bool {
+        self.0.ct_eq(other).into()
+    }
+}
+
+impl PartialEq for SecretBytes {
+    fn eq(&self, other: &Self) -> bool {
+        *self == other.0[..]
+    }
+}
+
+impl Eq for SecretBytes {}
+
+impl PartialEq<Vec<u8>> for SecretBytes {
+    fn eq(&self, other: &Vec<u8>) -> bool {
+        *self == other[..]
+    }
+}
+
+impl PartialEq<&[u8]> for SecretBytes {
+    fn eq(&self, other: &&[u8]) -> bool {
+        *self == **other
+    }
+}
+
+impl PartialEq<SecretBytes> for [u8] {
+    fn eq(&self, other: &SecretBytes) -> bool {
+        other == self
+    }
+}
+
+impl<const N: usize> PartialEq<[u8; N]> for SecretBytes {
+    fn eq(&self, other: &[u8; N]) -> bool {
+        *self == other[..]
+    }
+}
+
+impl<const N: usize> PartialEq<&[u8; N]> for SecretBytes {
+    fn eq(&self, other: &&[u8; N]) -> bool {
+        *self == other[..]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacted_debug() {
+        let password = SecretString::from("My_secr3tP@55w0rd");
+        let data = SecretBytes::from(b"Lorem ipsum".to_vec());
+
+        assert!(!format!("{:?}", password).contains("P@55"));
+        assert!(!format!("{:?}", data).contains("76"));
+        assert_eq!(format!("{:?}", Some(data)), "Some(SecretBytes([REDACTED]))");
+    }
+
+    #[test]
+    fn compare_and_serialize() {
+        let data = SecretBytes::from(b"Lorem ipsum".to_vec());
+        assert_eq!(data, b"Lorem ipsum");
+        assert_eq!(data, b"Lorem ipsum".to_vec());
+        assert_ne!(data, b"Lorem");
+
+        let password = SecretString::from("My_secr3tP@55w0rd");
+        let serialized = bincode::serialize(&password).unwrap();
+        // Same layout as a plain `String`
+        assert_eq!(serialized, bincode::serialize("My_secr3tP@55w0rd").unwrap());
+        let deserialized: SecretString = bincode::deserialize(&serialized).unwrap();
+        assert_eq!(deserialized, password);
+    }
+}
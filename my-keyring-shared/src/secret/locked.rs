@@ -0,0 +1,328 @@
+#[cfg(target_os = "linux")]
+use core::convert::TryFrom;
+use core::{
+    mem::{self, ManuallyDrop},
+    ops::{Deref, DerefMut},
+    ptr::{self, NonNull},
+    slice,
+};
+use std::alloc::{self, Layout};
+
+use zeroize::Zeroize;
+
+use crate::MyKeyringError;
+
+/// Key material kept out of the swap and of the core dumps, wiped when dropped
+///
+/// On Linux, the value is stored in its own pages, surrounded by inaccessible
+/// guard pages, locked in memory with `mlock` and excluded from the core dumps
+/// with `MADV_DONTDUMP`. If the pages cannot be locked, for instance because
+/// of the `RLIMIT_MEMLOCK` limit, or on the other systems, the value is still
+/// stored and wiped, but may be swapped: see [`Locked::ensure_locked`].
+///
+/// # Examples
+///
+/// ```
+/// use my_keyring_shared::secret::Locked;
+///
+/// let mut key = Locked::new([0_u8; 32]);
+/// key[0] = 42;
+///
+/// // Nothing to do, the value is usable anyway
+/// if key.ensure_locked().is_err() {
+///     eprintln!("the key may be swapped to disk");
+/// }
+/// assert_eq!(key[0], 42);
+/// ```
+pub struct Locked<T> {
+    /// The value, in the mapping or on the heap
+    value: NonNull<T>,
+    /// The pages holding the value, none if allocated on the heap
+    mapping: Option<Mapping>,
+    /// Whether the pages of the value are locked in memory
+    locked: bool,
+}
+
+// SAFETY: the value is owned like in a `Box`, only reachable through this
+// `Locked`, so it can be sent or shared when `T` can
+unsafe impl<T: Send> Send for Locked<T> {}
+unsafe impl<T: Sync> Sync for Locked<T> {}
+
+impl<T> Locked<T> {
+    /// Move the `value` into locked memory, falling back to the heap if the
+    /// memory cannot be locked
+    ///
+    /// The bytes of the `value` moved in are wiped.
+    pub fn new(value: T) -> Self {
+        let (pointer, mapping) = match Mapping::new(Layout::new::<T>()) {
+            Some((mapping, data)) => (data.cast(), Some(mapping)),
+            None => (heap_alloc(Layout::new::<T>()), None),
+        };
+        let locked = mapping.as_ref().is_some_and(Mapping::is_locked);
+
+        let mut value = ManuallyDrop::new(value);
+        // SAFETY: `pointer` is valid for writes of a `T` and aligned for it,
+        // from `Mapping::new` or `heap_alloc` with `Layout::new::<T>()`, and
+        // does not overlap the `value` on the stack. The moved out `value` is
+        // never dropped, its bytes are only wiped.
+        unsafe {
+            ptr::copy_nonoverlapping(&*value as *const T, pointer.as_ptr(), 1);
+            bytes_of(&mut *value).zeroize();
+        }
+
+        Self {
+            value: pointer,
+            mapping,
+            locked,
+        }
+    }
+
+    /// Move the `value` into locked memory
+    ///
+    /// # Errors
+    ///
+    /// Return [`MyKeyringError::MemoryNotLocked`] if the memory cannot be
+    /// locked, the `value` being wiped
+    pub fn try_new(value: T) -> crate::Result<Self> {
+        let locked = Self::new(value);
+        locked.ensure_locked()?;
+
+        Ok(locked)
+    }
+
+    /// Check that the value is locked in memory
+    ///
+    /// # Errors
+    ///
+    /// Return [`MyKeyringError::MemoryNotLocked`] if the value may be swapped
+    /// to disk
+    pub fn ensure_locked(&self) -> crate::Result<()> {
+        if self.locked {
+            Ok(())
+        } else {
+            Err(MyKeyringError::MemoryNotLocked)
+        }
+    }
+}
+
+impl<T> Drop for Locked<T> {
+    fn drop(&mut self) {
+        // SAFETY: the value is initialised since `Locked::new`, and dropped
+        // only once, here. Its bytes are then wiped but never read as a `T`,
+        // the memory being released right after.
+        unsafe {
+            ptr::drop_in_place(self.value.as_ptr());
+            bytes_of(self.value.as_mut()).zeroize();
+        }
+        if self.mapping.is_none() {
+            heap_dealloc(self.value.cast(), Layout::new::<T>());
+        }
+        // The mapping is released when dropped
+    }
+}
+
+impl<T> Deref for Locked<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        // SAFETY: the value is initialised, aligned and lives as long as
+        // `self`, which lends it
+        unsafe { self.value.as_ref() }
+    }
+}
+
+impl<T> DerefMut for Locked<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        // SAFETY: as for `deref`, and `&mut self` makes the borrow unique
+        unsafe { self.value.as_mut() }
+    }
+}
+
+/// The bytes of a `value`, to be wiped
+///
+/// # Safety
+///
+/// The value must not be used as a `T` once wiped, except to be deallocated.
+unsafe fn bytes_of<T>(value: &mut T) -> &mut [u8] {
+    // SAFETY: the `size_of::<T>()` bytes of the value are borrowed for as long
+    // as the value itself, and any byte is a valid `u8`. Padding bytes may be
+    // uninitialised, but they are only overwritten.
+    slice::from_raw_parts_mut((value as *mut T).cast(), mem::size_of::<T>())
+}
+
+/// Allocate the `layout` on the heap, a dangling pointer if zero sized
+fn heap_alloc<T>(layout: Layout) -> NonNull<T> {
+    if layout.size() == 0 {
+        return NonNull::dangling();
+    }
+    // SAFETY: the size of the `layout` is not zero
+    let pointer = unsafe { alloc::alloc(layout) };
+    NonNull::new(pointer)
+        .unwrap_or_else(|| alloc::handle_alloc_error(layout))
+        .cast()
+}
+
+/// Release the `layout` allocated by [`heap_alloc`]
+fn heap_dealloc(pointer: NonNull<u8>, layout: Layout) {
+    if layout.size() != 0 {
+        // SAFETY: the `pointer` was allocated by `heap_alloc` with the same
+        // non zero sized `layout`, and is released only once
+        unsafe { alloc::dealloc(pointer.as_ptr(), layout) };
+    }
+}
+
+/// Anonymous pages holding a value, between two guard pages
+#[cfg(target_os = "linux")]
+struct Mapping {
+    /// Start of the mapping, the first guard page
+    base: NonNull<u8>,
+    /// Length of the whole mapping, guard pages included
+    length: usize,
+    /// Length of the data pages, between the guard pages
+    data_length: usize,
+    /// Whether the data pages are locked in memory
+    locked: bool,
+}
+
+#[cfg(target_os = "linux")]
+impl Mapping {
+    /// Map pages for the `layout`, returning the mapping and the start of the
+    /// data pages, or none if the pages cannot be mapped
+    fn new(layout: Layout) -> Option<(Self, NonNull<u8>)> {
+        // SAFETY: `sysconf` has no precondition
+        let page = usize::try_from(unsafe { libc::sysconf(libc::_SC_PAGESIZE) }).ok()?;
+        if layout.align() > page {
+            return None;
+        }
+        let data_length = layout.size().max(1).checked_add(page - 1)? / page * page;
+        let length = data_length.checked_add(2 * page)?;
+
+        // SAFETY: a new private anonymous mapping, at an address chosen by the
+        // kernel, so no existing memory is affected
+        let base = unsafe {
+            libc::mmap(
+                ptr::null_mut(),
+                length,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+                -1,
+                0,
+            )
+        };
+        if base == libc::MAP_FAILED {
+            return None;
+        }
+        let mut mapping = Self {
+            base: NonNull::new(base.cast())?,
+            length,
+            data_length,
+            locked: false,
+        };
+        // SAFETY: the mapping is `length` bytes long, so one page in stays in
+        // it. The mapping is page aligned, and the `layout` alignment is at
+        // most a page, so `data` is aligned for it.
+        let data = unsafe { base.cast::<u8>().add(page) };
+
+        // Any overflow or underflow of the value hits a guard page
+        // SAFETY: both guard pages, the first page and the one following the
+        // `data_length` bytes of data, are in the mapping and page aligned
+        let guards = unsafe {
+            libc::mprotect(base, page, libc::PROT_NONE) == 0
+                && libc::mprotect(data.add(data_length).cast(), page, libc::PROT_NONE) == 0
+        };
+        if !guards {
+            return None;
+        }
+        // SAFETY: the data pages are in the mapping, start on a page boundary
+        // and are `data_length` bytes long, a multiple of the page size
+        unsafe {
+            // Best effort, not supported before Linux 3.4
+            libc::madvise(data.cast(), data_length, libc::MADV_DONTDUMP);
+            mapping.locked = libc::mlock(data.cast(), data_length) == 0;
+        }
+
+        Some((mapping, NonNull::new(data)?))
+    }
+
+    /// Return whether the data pages are locked in memory
+    #[inline]
+    fn is_locked(&self) -> bool {
+        self.locked
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Drop for Mapping {
+    fn drop(&mut self) {
+        // SAFETY: the mapping, and its data pages between the two guard
+        // pages, were mapped by `Mapping::new` with these lengths. The value
+        // they held is already dropped and wiped by `Locked`, and nothing
+        // points into them once unmapped.
+        unsafe {
+            if self.locked {
+                let page = (self.length - self.data_length) / 2;
+                libc::munlock(self.base.as_ptr().add(page).cast(), self.data_length);
+            }
+            libc::munmap(self.base.as_ptr().cast(), self.length);
+        }
+    }
+}
+
+/// Without support of locked pages, the values are on the heap and no mapping
+/// can exist
+#[cfg(not(target_os = "linux"))]
+enum Mapping {}
+
+#[cfg(not(target_os = "linux"))]
+impl Mapping {
+    /// Never available
+    fn new(_layout: Layout) -> Option<(Self, NonNull<u8>)> {
+        None
+    }
+
+    /// Never called, no mapping existing
+    fn is_locked(&self) -> bool {
+        match *self {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+
+    #[test]
+    fn holds_the_value() {
+        let mut key = Locked::new([7_u8; 100]);
+        assert_eq!(key[99], 7);
+        key[0] = 1;
+        assert_eq!(key[..2], [1, 7]);
+
+        // Larger than a page, and zero sized
+        let big = Locked::new([3_u64; 1024]);
+        assert!(big.iter().all(|&value| value == 3));
+        let _unit = Locked::new(());
+
+        // Locked on Linux, unless the limit is reached
+        if cfg!(not(target_os = "linux")) {
+            assert_eq!(
+                key.ensure_locked().unwrap_err(),
+                MyKeyringError::MemoryNotLocked
+            );
+        }
+        assert_eq!(key.ensure_locked().is_ok(), key.locked);
+    }
+
+    #[test]
+    fn drops_the_value() {
+        let counter = Rc::new(());
+        let locked = Locked::new(Rc::clone(&counter));
+        assert_eq!(Rc::strong_count(&counter), 2);
+
+        drop(locked);
+        assert_eq!(Rc::strong_count(&counter), 1);
+    }
+}
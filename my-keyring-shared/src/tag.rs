@@ -1,12 +1,20 @@
+//! Contains the tags, labels shared by the entries of a vault
+//!
+//! The names are held by the [`TagPool`], the entries only reference their
+//! identifiers through the [`Tags`] trait.
+
+use serde::{Deserialize, Serialize};
 use ulid::Ulid;
 
-#[derive(Debug, Clone)]
+/// A named label
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Tag {
     id: Ulid,
     name: String,
 }
 
 impl Tag {
+    /// Create the tag `name`, with a new identifier
     pub fn new(name: &str) -> Self {
         Self {
             id: Ulid::new(),
@@ -14,34 +22,41 @@ impl Tag {
         }
     }
 
+    /// Rename the tag
     pub fn set_name(&mut self, new_name: &str) {
         self.name = new_name.to_owned();
     }
 
+    /// Return the name
     pub fn get_name(&self) -> &String {
         &self.name
     }
 
+    /// Return the identifier
     pub fn get_id(&self) -> Ulid {
         self.id
     }
 }
 
-#[derive(Debug, Clone)]
+/// The tags of a vault, unique by name
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct TagPool {
     tags: Vec<Tag>,
 }
 
 impl TagPool {
+    /// Add a new tag `name`, replacing the existing one
     pub fn add_tag(&mut self, name: &str) {
         self.del_tag(name);
         self.tags.push(Tag::new(name));
     }
 
+    /// Remove the tag `name`
     pub fn del_tag(&mut self, name: &str) {
         self.tags.retain(|t| t.name != name);
     }
 
+    /// Return the identifier of the tag `name`
     pub fn get_tag_id(&self, name: &str) -> Option<Ulid> {
         for tag in &self.tags {
             if tag.get_name() == name {
@@ -52,17 +67,33 @@ impl TagPool {
     }
 }
 
+/// An entry that can be tagged with identifiers of the [`TagPool`]
 pub trait Tags {
+    /// Return the tag identifiers, to modify them
     fn tags(&mut self) -> &mut Vec<Ulid>;
 
+    /// Called once a tag has actually been added or removed, to record the
+    /// modification
+    #[inline]
+    fn tags_changed(&mut self) {}
+
+    /// Add the tag `tag_id`, once
     #[inline]
     fn add_tag(&mut self, tag_id: Ulid) {
-        self.del_tag(tag_id);
-        self.tags().push(tag_id);
+        if !self.tags().contains(&tag_id) {
+            self.tags().push(tag_id);
+            self.tags_changed();
+        }
     }
 
+    /// Remove the tag `tag_id`
     #[inline]
     fn del_tag(&mut self, tag_id: Ulid) {
-        self.tags().retain(|t| t != &tag_id);
+        let tags = self.tags();
+        let count = tags.len();
+        tags.retain(|t| t != &tag_id);
+        if tags.len() != count {
+            self.tags_changed();
+        }
     }
 }
@@ -33,10 +33,18 @@
 
 use std::time::SystemTime;
 
-use base32::{decode, Alphabet};
+use base32::{decode, encode, Alphabet};
+use rand_core::{OsRng, RngCore};
 use serde::{Deserialize, Serialize};
+use subtle::ConstantTimeEq;
 
-use crate::{algo::Algorithm, errors::MyKeyringError};
+use crate::{
+    algo::Algorithm,
+    errors::MyKeyringError,
+    secret::{SecretBytes, SecretString},
+};
+
+mod uri;
 
 /// Decode a base32 encoded string, removing padding and optional `-` (dash) or
 /// `space`
@@ -68,6 +76,53 @@ pub fn decode_base32(input: &str) -> crate::Result<Vec<u8>> {
     decode(Alphabet::RFC4648 { padding: false }, &encoded).ok_or(MyKeyringError::InvalidBase32)
 }
 
+/// Encode `data` as an unpadded base32 string, optionally split in groups of
+/// `group` characters separated by a space for readability
+///
+/// The result can be decoded with [`decode_base32`], with or without groups.
+///
+/// # Examples
+///
+/// ```
+/// use my_keyring_shared::totp::{decode_base32, encode_base32};
+/// # fn main() -> my_keyring_shared::Result<()> {
+/// assert_eq!(encode_base32(b"Hello world!", None), "JBSWY3DPEB3W64TMMQQQ");
+///
+/// let grouped = encode_base32(b"Hello world!", 4);
+/// assert_eq!(grouped, "JBSW Y3DP EB3W 64TM MQQQ");
+/// assert_eq!(decode_base32(&grouped)?, b"Hello world!");
+/// # Ok(())
+/// # }
+/// ```
+pub fn encode_base32(data: &[u8], group: impl Into<Option<usize>>) -> String {
+    let encoded = encode(Alphabet::RFC4648 { padding: false }, data);
+
+    match group.into().filter(|g| *g > 0) {
+        Some(group) => encoded
+            .as_bytes()
+            .chunks(group)
+            .map(|chunk| std::str::from_utf8(chunk).expect("base32 is ascii"))
+            .collect::<Vec<_>>()
+            .join(" "),
+        None => encoded,
+    }
+}
+
+/// Characters used to render a Steam Guard code
+const STEAM_ALPHABET: &str = "23456789BCDFGHJKMNPQRTVWXY";
+/// Maximal number of digits, the truncated value having 10 decimal digits
+pub(crate) const MAX_DIGITS: u8 = 10;
+
+/// How the truncated value is rendered into a code
+#[derive(Debug, PartialEq, Copy, Clone, Default, Serialize, Deserialize)]
+pub enum Encoding {
+    /// Decimal digits, as defined in RFC 6238
+    #[default]
+    Decimal,
+    /// Steam Guard alphanumeric characters
+    Steam,
+}
+
 /// Generate a Totp, based either on the current timestamp, or arbitrary value
 ///
 /// # Examples
@@ -84,16 +139,25 @@ pub fn decode_base32(input: &str) -> crate::Result<Vec<u8>> {
 /// # Ok(())
 /// # }
 /// ```
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Totp {
     /// Secret to use
-    secret: String,
+    secret: SecretString,
     /// Number of digits, 6 (default) or 8
     digits: u8,
     /// Period of validity of the token (30 secs by default)
     period: u32,
     /// Algorithm to use during Totp generation
     algorithm: Algorithm,
+    /// Provider or service the account belongs to
+    #[serde(default)]
+    issuer: Option<String>,
+    /// Name of the account, generally a username or an email
+    #[serde(default)]
+    account: Option<String>,
+    /// Rendering of the code, decimal digits by default
+    #[serde(default)]
+    encoding: Encoding,
 }
 
 impl Totp {
@@ -102,7 +166,7 @@ impl Totp {
     /// # Defaults values
     ///
     /// The `secret` is indicated from the website,
-    /// `digits` is the desired length, defaulting to 6,
+    /// `digits` is the desired length, defaulting to 6, between 1 and 10,
     /// `period` is the window timestamp validity, defaulting to 30 seconds,
     /// `algorithm` is the algorithm used to generate, defaulting to Sha-1.
     ///
@@ -136,13 +200,110 @@ impl Totp {
         let _ = decode_base32(secret)?;
 
         Ok(Self {
-            secret: secret.to_owned(),
-            digits: digits.into().unwrap_or(6).max(1),
+            secret: secret.into(),
+            digits: digits.into().unwrap_or(6).clamp(1, MAX_DIGITS),
             period: period.into().unwrap_or(30).max(1),
             algorithm: algorithm.into().unwrap_or(Algorithm::Sha1),
+            issuer: None,
+            account: None,
+            encoding: Encoding::Decimal,
         })
     }
 
+    /// Initialise a new Totp with a random secret, to provision a new account
+    ///
+    /// The secret is drawn from the operating system random number generator,
+    /// and is as long as the `algorithm` digest. The other parameters follow
+    /// the [`Totp::new`] defaults.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use my_keyring_shared::{totp::Totp, Algorithm};
+    ///
+    /// let totp = Totp::generate(Algorithm::Sha256, None, None);
+    /// // 32 bytes secret, so 52 base32 characters
+    /// assert_eq!(totp.secret().len(), 52);
+    /// # println!("{}", totp.to_uri());
+    /// ```
+    pub fn generate(
+        algorithm: impl Into<Option<Algorithm>>,
+        digits: impl Into<Option<u8>>,
+        period: impl Into<Option<u32>>,
+    ) -> Self {
+        let algorithm = algorithm.into().unwrap_or(Algorithm::Sha1);
+
+        let mut secret = vec![0; algorithm.digest_length()];
+        OsRng::fill_bytes(&mut OsRng, &mut secret[..]);
+
+        Self::new(&encode_base32(&secret, None), digits, period, algorithm)
+            .expect("valid base32 secret")
+    }
+
+    /// Return the base32 encoded secret, as provided or generated
+    #[inline]
+    pub fn secret(&self) -> &str {
+        &self.secret
+    }
+
+    /// Initialise a new Steam Guard Totp
+    ///
+    /// Steam uses Sha-1 with a 30 seconds period, but renders 5 characters
+    /// from its own alphabet instead of decimal digits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> my_keyring_shared::Result<()> {
+    /// use my_keyring_shared::totp::Totp;
+    /// let totp = Totp::new_steam("GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ")?;
+    ///
+    /// assert_eq!("VHHQY", totp.totp_from_timestamp(1_234_567_890));
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Return [`MyKeyringError::InvalidBase32`] error if the provided `secret`
+    /// is not a valid base32 encoded string
+    pub fn new_steam(secret: &str) -> crate::Result<Self> {
+        let mut totp = Self::new(secret, 5, 30, Algorithm::Sha1)?;
+        totp.encoding = Encoding::Steam;
+        Ok(totp)
+    }
+
+    /// Return how the codes are rendered
+    #[inline]
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    /// Change how the codes are rendered, the `digits` value being used as the
+    /// number of characters
+    #[inline]
+    pub fn set_encoding(&mut self, encoding: Encoding) {
+        self.encoding = encoding;
+    }
+
+    /// Return the issuer, the provider or service of the account, if any
+    #[inline]
+    pub fn issuer(&self) -> Option<&str> {
+        self.issuer.as_deref()
+    }
+
+    /// Return the account name, if any
+    #[inline]
+    pub fn account(&self) -> Option<&str> {
+        self.account.as_deref()
+    }
+
+    /// Set the `issuer` and the `account` name displayed alongside the codes
+    pub fn set_label(&mut self, issuer: Option<&str>, account: Option<&str>) {
+        self.issuer = issuer.map(ToOwned::to_owned);
+        self.account = account.map(ToOwned::to_owned);
+    }
+
     /// Retrieve the Totp value based on current timestamp
     ///
     /// # Examples
@@ -181,28 +342,185 @@ impl Totp {
     /// ```
     pub fn totp_from_timestamp(&self, timestamp: u64) -> String {
         // Generate the counter based on the period window
-        let counter = timestamp / u64::from(self.period);
+        self.totp_from_step(self.current_step(timestamp))
+    }
+
+    /// Return the time step of the `timestamp`, the number of periods elapsed
+    /// since the Unix epoch
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> my_keyring_shared::Result<()> {
+    /// use my_keyring_shared::totp::Totp;
+    /// let totp = Totp::new("JBSWY3DPEB3W64TMMQQQ", None, None, None)?;
+    ///
+    /// assert_eq!(41_152_263, totp.current_step(1_234_567_890));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn current_step(&self, timestamp: u64) -> u64 {
+        timestamp / u64::from(self.period)
+    }
+
+    /// Return the number of seconds the code of the `timestamp` stays valid,
+    /// between 1 and the period
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> my_keyring_shared::Result<()> {
+    /// use my_keyring_shared::totp::Totp;
+    /// let totp = Totp::new("JBSWY3DPEB3W64TMMQQQ", None, None, None)?;
+    ///
+    /// // The code changes at 1234567890 + 30
+    /// assert_eq!(30, totp.remaining_seconds(1_234_567_890));
+    /// assert_eq!(1, totp.remaining_seconds(1_234_567_919));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn remaining_seconds(&self, timestamp: u64) -> u32 {
+        let period = u64::from(self.period);
+        (period - timestamp % period) as u32
+    }
+
+    /// Iterate over the codes, starting with the one of the `timestamp`, then
+    /// the upcoming ones
+    ///
+    /// Each item is the timestamp from which the code is valid, and the code.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> my_keyring_shared::Result<()> {
+    /// use my_keyring_shared::totp::Totp;
+    /// let totp = Totp::new("JFIFCUSTKRKVMV2IJFIFCUSTKRKVMV2I", None, None, None)?;
+    ///
+    /// let mut codes = totp.codes(1_234_567_890);
+    /// assert_eq!(Some((1_234_567_890, "850592".to_owned())), codes.next());
+    /// assert_eq!(1_234_567_920, codes.next().unwrap().0);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn codes(&self, timestamp: u64) -> Codes<'_> {
+        Codes {
+            totp: self,
+            step: Some(self.current_step(timestamp)),
+        }
+    }
+
+    /// Check that `code` is valid for the `timestamp`, accepting up to
+    /// `skew_steps` periods before or after it to compensate clock drift
+    ///
+    /// Codes are compared in constant time. The matching time step is returned,
+    /// so the caller can store it and reject any later code from a step that
+    /// is not strictly greater, preventing replays.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> my_keyring_shared::Result<()> {
+    /// use my_keyring_shared::totp::Totp;
+    /// let totp = Totp::new("JFIFCUSTKRKVMV2IJFIFCUSTKRKVMV2I", None, None, None)?;
+    ///
+    /// // Code generated 30 seconds ago
+    /// assert_eq!(Some(41_152_263), totp.verify("850592", 1_234_567_920, 1));
+    /// // Too old for the window
+    /// assert_eq!(None, totp.verify("850592", 1_234_567_950, 1));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn verify(&self, code: &str, timestamp: u64, skew_steps: u64) -> Option<u64> {
+        let current = self.current_step(timestamp);
+
+        (current.saturating_sub(skew_steps)..=current.saturating_add(skew_steps))
+            .find(|&step| constant_time_eq(&self.totp_from_step(step), code))
+    }
 
+    /// Retrieve the Totp of the time `step`, the number of periods elapsed
+    /// since the Unix epoch
+    fn totp_from_step(&self, counter: u64) -> String {
         // Compute the Hmac
         let digest = self.algorithm.hmac(
-            &decode_base32(&self.secret).expect("Base32 decoded string"),
+            &SecretBytes::from(decode_base32(&self.secret).expect("Base32 decoded string")),
             &counter.to_be_bytes(),
         );
 
-        // Truncate
-        let offset = (digest.last().expect("last array member") & 0xf) as usize;
-        let binary = (u64::from(digest[offset]) & 0x7f) << 24
-            | (u64::from(digest[offset + 1]) & 0xff) << 16
-            | (u64::from(digest[offset + 2]) & 0xff) << 8
-            | u64::from(digest[offset + 3]) & 0xff;
-        let binary = binary % (10_u64.pow(self.digits.into()));
+        // Truncate, then render using the desired encoding
+        let binary = dynamic_truncation(&digest);
+        match self.encoding {
+            Encoding::Decimal => decimal_code(binary, self.digits),
+            Encoding::Steam => steam_code(binary, self.digits),
+        }
+    }
+}
+
+/// Iterator over the successive codes of a [`Totp`], created by [`Totp::codes`]
+#[derive(Debug)]
+pub struct Codes<'a> {
+    /// Totp generating the codes
+    totp: &'a Totp,
+    /// Next time step to generate, `None` once the timestamps overflow
+    step: Option<u64>,
+}
+
+impl Iterator for Codes<'_> {
+    type Item = (u64, String);
 
-        // Prepend with additional 0 to have digits length Token and convert it to
-        // String
-        format!("{:0>1$}", binary, usize::from(self.digits))
+    fn next(&mut self) -> Option<Self::Item> {
+        let step = self.step?;
+        let step_start = step.checked_mul(u64::from(self.totp.period))?;
+
+        self.step = step.checked_add(1);
+        Some((step_start, self.totp.totp_from_timestamp(step_start)))
     }
 }
 
+/// Extract a 31 bits value from an HMAC `digest`, using the dynamic truncation
+/// defined in RFC 4226 section 5.3
+pub(crate) fn dynamic_truncation(digest: &[u8]) -> u64 {
+    let offset = (digest.last().expect("last array member") & 0xf) as usize;
+    (u64::from(digest[offset]) & 0x7f) << 24
+        | (u64::from(digest[offset + 1]) & 0xff) << 16
+        | (u64::from(digest[offset + 2]) & 0xff) << 8
+        | u64::from(digest[offset + 3]) & 0xff
+}
+
+/// Render the truncated `binary` value as a `digits` length Steam Guard code
+fn steam_code(mut binary: u64, digits: u8) -> String {
+    let alphabet = STEAM_ALPHABET.as_bytes();
+    let len = alphabet.len() as u64;
+
+    (0..digits)
+        .map(|_| {
+            let c = char::from(alphabet[(binary % len) as usize]);
+            binary /= len;
+            c
+        })
+        .collect()
+}
+
+/// Compare two codes without leaking the position of the first difference
+pub(crate) fn constant_time_eq(code: &str, expected: &str) -> bool {
+    code.as_bytes().ct_eq(expected.as_bytes()).into()
+}
+
+/// Reduce the truncated `binary` value to a `digits` length decimal code
+pub(crate) fn decimal_code(binary: u64, digits: u8) -> String {
+    // Deserialized values may exceed the 10 digits of the truncated value
+    let binary = match 10_u64.checked_pow(digits.into()) {
+        Some(modulus) => binary % modulus,
+        None => binary,
+    };
+
+    // Prepend with additional 0 to have digits length Token and convert it to
+    // String
+    format!("{:0>1$}", binary, usize::from(digits))
+}
+
 #[cfg(test)]
 mod tests {
     use test::Bencher;
@@ -231,6 +549,39 @@ mod tests {
         assert_eq!(decode_base32("MZXW6YTBOI").unwrap(), b"foobar");
     }
 
+    #[test]
+    fn base32_encoding() {
+        assert_eq!(encode_base32(b"", None), "");
+        assert_eq!(encode_base32(b"f", None), "MY");
+        assert_eq!(encode_base32(b"foobar", None), "MZXW6YTBOI");
+        assert_eq!(encode_base32(b"foobar", 0), "MZXW6YTBOI");
+        assert_eq!(encode_base32(b"foobar", 3), "MZX W6Y TBO I");
+        assert_eq!(encode_base32(b"foobar", 20), "MZXW6YTBOI");
+    }
+
+    #[test]
+    fn generate_secret_length() -> crate::Result<()> {
+        for &(algorithm, length) in &[
+            (Algorithm::Sha1, 20),
+            (Algorithm::Sha256, 32),
+            (Algorithm::Sha512, 64),
+        ] {
+            let t = Totp::generate(algorithm, 8, 60);
+            assert_eq!(decode_base32(t.secret())?.len(), length);
+            assert_eq!(t.algorithm, algorithm);
+            assert_eq!(t.digits, 8);
+            assert_eq!(t.period, 60);
+        }
+
+        // Two secrets are never the same
+        assert_ne!(
+            Totp::generate(None, None, None).secret(),
+            Totp::generate(None, None, None).secret()
+        );
+
+        Ok(())
+    }
+
     #[bench]
     fn bench_totp_sha1_8chars(b: &mut Bencher) {
         // 12345678901234567890
@@ -285,6 +636,98 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn steam_encoding() -> crate::Result<()> {
+        // 12345678901234567890
+        let seed = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+        let t = Totp::new_steam(seed)?;
+        assert_eq!(t.encoding(), Encoding::Steam);
+        assert_eq!(t.totp_from_timestamp(59), "PV9M4");
+        assert_eq!(t.totp_from_timestamp(1_234_567_890), "VHHQY");
+        assert_eq!(t.totp_from_timestamp(2_000_000_000), "9N776");
+        assert_eq!(t.verify("VHHQY", 1_234_567_890, 0), Some(41_152_263));
+
+        // Only the rendering differs from the decimal one
+        let mut t = Totp::new(seed, 5, None, None)?;
+        assert_eq!(t.totp_from_timestamp(59), "87082");
+        t.set_encoding(Encoding::Steam);
+        assert_eq!(t.totp_from_timestamp(59), "PV9M4");
+
+        Ok(())
+    }
+
+    #[test]
+    fn countdown() -> crate::Result<()> {
+        let t = Totp::new("GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ", None, 60, None)?;
+        assert_eq!(t.current_step(0), 0);
+        assert_eq!(t.current_step(59), 0);
+        assert_eq!(t.current_step(60), 1);
+        assert_eq!(t.remaining_seconds(0), 60);
+        assert_eq!(t.remaining_seconds(59), 1);
+        assert_eq!(t.remaining_seconds(60), 60);
+        assert_eq!(t.remaining_seconds(u64::MAX), 60 - (u64::MAX % 60) as u32);
+
+        Ok(())
+    }
+
+    #[test]
+    fn upcoming_codes() -> crate::Result<()> {
+        // 12345678901234567890
+        let seed = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+        let t = Totp::new(seed, 8, None, None)?;
+
+        let codes: Vec<_> = t.codes(1_111_111_109).take(2).collect();
+        assert_eq!(
+            codes,
+            vec![
+                (1_111_111_080, "07081804".to_owned()),
+                (1_111_111_110, "14050471".to_owned())
+            ]
+        );
+
+        // Stop once the timestamps cannot be represented
+        assert_eq!(t.codes(u64::MAX).count(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn verify_with_skew() -> crate::Result<()> {
+        // 12345678901234567890
+        let seed = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+        let t = Totp::new(seed, 8, None, None)?;
+
+        // Exact step
+        assert_eq!(t.verify("89005924", 1_234_567_890, 0), Some(41_152_263));
+        // Previous and next steps
+        assert_eq!(t.verify("89005924", 1_234_567_920, 1), Some(41_152_263));
+        assert_eq!(t.verify("89005924", 1_234_567_860, 1), Some(41_152_263));
+        // Out of the window
+        assert_eq!(t.verify("89005924", 1_234_567_920, 0), None);
+        assert_eq!(t.verify("89005924", 1_234_567_950, 1), None);
+        // Invalid codes
+        assert_eq!(t.verify("89005925", 1_234_567_890, 1), None);
+        assert_eq!(t.verify("8900592", 1_234_567_890, 1), None);
+        // Near the epoch, the window is clamped
+        assert_eq!(t.verify("94287082", 0, 5), Some(1));
+
+        Ok(())
+    }
+
+    #[test]
+    fn digits_out_of_range() -> crate::Result<()> {
+        let seed = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+        let t = Totp::new(seed, 20, None, None)?;
+        assert_eq!(t.digits, MAX_DIGITS);
+        assert_eq!(t.totp_from_timestamp(59), "1094287082");
+        assert_eq!(Totp::new(seed, 0, None, None)?.digits, 1);
+
+        // Values out of the constructors range do not panic
+        assert_eq!(decimal_code(1_094_287_082, u8::MAX).len(), 255);
+
+        Ok(())
+    }
+
     #[test]
     fn tests_vectors_rfc4226_sha1_6chars() -> crate::Result<()> {
         // These are normally HTOP test vectors, but can be used if `period` is one
@@ -0,0 +1,372 @@
+//! Key URI Format, as used in the enrollment QR codes:
+//! `otpauth://totp/Issuer:account?secret=...&issuer=...&digits=...`
+
+use crate::{
+    algo::Algorithm,
+    errors::MyKeyringError,
+    totp::{Encoding, Totp, MAX_DIGITS},
+};
+
+/// Scheme and type prefix of a Totp URI
+const TOTP_PREFIX: &str = "otpauth://totp/";
+
+impl Totp {
+    /// Parse an `otpauth://totp/` URI, as found in the enrollment QR codes
+    ///
+    /// The label is either `Issuer:account` or `account`, the `issuer`
+    /// parameter taking precedence over the label one when both are present.
+    /// Missing `digits`, `period` and `algorithm` parameters fall back to the
+    /// [`Totp::new`] defaults, unknown parameters are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> my_keyring_shared::Result<()> {
+    /// use my_keyring_shared::totp::Totp;
+    /// let totp = Totp::from_uri(
+    ///     "otpauth://totp/ACME%20Co:john.doe@email.com?secret=JBSWY3DPEB3W64TMMQQQ&issuer=ACME%20Co&\
+    ///      period=60",
+    /// )?;
+    ///
+    /// assert_eq!(Some("ACME Co"), totp.issuer());
+    /// assert_eq!(Some("john.doe@email.com"), totp.account());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// - [`MyKeyringError::InvalidUriScheme`] if the URI does not start with
+    ///   `otpauth://`,
+    /// - [`MyKeyringError::UnsupportedOtpType`] if the type is not `totp`,
+    /// - [`MyKeyringError::MissingSecret`] if there is no `secret` parameter,
+    /// - [`MyKeyringError::InvalidBase32`] if the `secret` is not valid base32,
+    /// - [`MyKeyringError::InvalidUriParameter`] if `digits`, `period` or
+    ///   `algorithm` has an invalid value,
+    /// - [`MyKeyringError::InvalidPercentEncoding`] if an escaped sequence
+    ///   cannot be decoded.
+    pub fn from_uri(uri: &str) -> crate::Result<Self> {
+        if !uri.starts_with(TOTP_PREFIX) {
+            return Err(if uri.starts_with("otpauth://") {
+                MyKeyringError::UnsupportedOtpType
+            } else {
+                MyKeyringError::InvalidUriScheme
+            });
+        }
+
+        let rest = &uri[TOTP_PREFIX.len()..];
+        let (label, query) = match rest.find('?') {
+            Some(pos) => (&rest[..pos], &rest[pos + 1..]),
+            None => (rest, ""),
+        };
+
+        // Label, either `Issuer:account` or `account`. The separator is searched
+        // before decoding, so an escaped colon can be part of the issuer, but
+        // fall back on an escaped separator as some generators encode it
+        let (mut issuer, account) = match label.find(':') {
+            Some(pos) => (
+                Some(percent_decode(&label[..pos], false)?),
+                percent_decode(&label[pos + 1..], false)?,
+            ),
+            None => {
+                let label = percent_decode(label, false)?;
+                match label.find(':') {
+                    Some(pos) => (Some(label[..pos].to_owned()), label[pos + 1..].to_owned()),
+                    None => (None, label),
+                }
+            }
+        };
+        let account = account.trim_start().to_owned();
+
+        let mut secret = None;
+        let mut digits = None;
+        let mut period = None;
+        let mut algorithm = None;
+        let mut encoding = Encoding::Decimal;
+        for parameter in query.split('&').filter(|p| !p.is_empty()) {
+            let (key, value) = match parameter.find('=') {
+                Some(pos) => (
+                    &parameter[..pos],
+                    percent_decode(&parameter[pos + 1..], true)?,
+                ),
+                None => (parameter, String::new()),
+            };
+            match key {
+                "secret" => secret = Some(value),
+                "issuer" => issuer = Some(value),
+                "digits" => {
+                    digits = Some(
+                        value
+                            .parse::<u8>()
+                            .ok()
+                            .filter(|d| (1..=MAX_DIGITS).contains(d))
+                            .ok_or(MyKeyringError::InvalidUriParameter)?,
+                    )
+                }
+                "period" => {
+                    period = Some(
+                        value
+                            .parse::<u32>()
+                            .ok()
+                            .filter(|p| *p > 0)
+                            .ok_or(MyKeyringError::InvalidUriParameter)?,
+                    )
+                }
+                "algorithm" => algorithm = Some(parse_algorithm(&value)?),
+                "encoder" => encoding = parse_encoder(&value)?,
+                _ => {}
+            }
+        }
+
+        let secret = secret
+            .filter(|s| !s.is_empty())
+            .ok_or(MyKeyringError::MissingSecret)?;
+        let mut totp = match encoding {
+            Encoding::Decimal => Self::new(&secret, digits, period, algorithm)?,
+            Encoding::Steam => {
+                let mut totp = Self::new_steam(&secret)?;
+                totp.digits = digits.unwrap_or(totp.digits);
+                totp.period = period.unwrap_or(totp.period);
+                totp.algorithm = algorithm.unwrap_or(totp.algorithm);
+                totp
+            }
+        };
+        totp.issuer = issuer.filter(|i| !i.is_empty());
+        totp.account = Some(account).filter(|a| !a.is_empty());
+
+        Ok(totp)
+    }
+
+    /// Generate the `otpauth://totp/` URI of this Totp, to be displayed as an
+    /// enrollment QR code
+    ///
+    /// All the parameters are always written, so the URI does not depend on
+    /// the defaults of the application reading it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> my_keyring_shared::Result<()> {
+    /// use my_keyring_shared::{totp::Totp, Algorithm};
+    /// let mut totp = Totp::new("JBSW Y3DP-EB3W 64TM-MQQQ", 8, None, Algorithm::Sha256)?;
+    /// totp.set_label(Some("ACME Co"), Some("john.doe@email.com"));
+    ///
+    /// assert_eq!(
+    ///     "otpauth://totp/ACME%20Co:john.doe%40email.com?secret=JBSWY3DPEB3W64TMMQQQ&issuer=ACME%\
+    ///      20Co&algorithm=SHA256&digits=8&period=30",
+    ///     totp.to_uri(),
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_uri(&self) -> String {
+        let mut uri = TOTP_PREFIX.to_owned();
+
+        if let Some(issuer) = &self.issuer {
+            uri.push_str(&percent_encode(issuer));
+            uri.push(':');
+        }
+        if let Some(account) = &self.account {
+            uri.push_str(&percent_encode(account));
+        }
+
+        let secret = self
+            .secret
+            .trim_end_matches('=')
+            .replace(&['-', ' '][..], "")
+            .to_ascii_uppercase();
+        uri.push_str("?secret=");
+        uri.push_str(&secret);
+        if let Some(issuer) = &self.issuer {
+            uri.push_str("&issuer=");
+            uri.push_str(&percent_encode(issuer));
+        }
+        uri.push_str("&algorithm=");
+        uri.push_str(algorithm_name(self.algorithm));
+        uri.push_str(&format!("&digits={}&period={}", self.digits, self.period));
+        if self.encoding == Encoding::Steam {
+            uri.push_str("&encoder=steam");
+        }
+
+        uri
+    }
+}
+
+/// Name of the `algorithm` in the URI
+fn algorithm_name(algorithm: Algorithm) -> &'static str {
+    match algorithm {
+        Algorithm::Sha1 => "SHA1",
+        Algorithm::Sha256 => "SHA256",
+        Algorithm::Sha512 => "SHA512",
+    }
+}
+
+/// Parse the `algorithm` parameter of the URI, ignoring case
+fn parse_algorithm(value: &str) -> crate::Result<Algorithm> {
+    match value.to_ascii_uppercase().as_str() {
+        "SHA1" => Ok(Algorithm::Sha1),
+        "SHA256" => Ok(Algorithm::Sha256),
+        "SHA512" => Ok(Algorithm::Sha512),
+        _ => Err(MyKeyringError::InvalidUriParameter),
+    }
+}
+
+/// Parse the `encoder` parameter of the URI, an extension used by some
+/// authenticators for the Steam Guard codes
+fn parse_encoder(value: &str) -> crate::Result<Encoding> {
+    match value.to_ascii_lowercase().as_str() {
+        "steam" => Ok(Encoding::Steam),
+        _ => Err(MyKeyringError::InvalidUriParameter),
+    }
+}
+
+/// Escape every character of `value` that is not unreserved, as defined in RFC
+/// 3986
+fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(char::from(byte))
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// Decode the `%XX` sequences of `value`, and the `+` as space if
+/// `plus_as_space` is set, as done in the query part of the URI
+fn percent_decode(value: &str, plus_as_space: bool) -> crate::Result<String> {
+    let mut decoded = Vec::with_capacity(value.len());
+    let mut bytes = value.bytes();
+    while let Some(byte) = bytes.next() {
+        match byte {
+            b'%' => {
+                let high = bytes.next().and_then(|c| char::from(c).to_digit(16));
+                let low = bytes.next().and_then(|c| char::from(c).to_digit(16));
+                match (high, low) {
+                    (Some(high), Some(low)) => decoded.push((high << 4 | low) as u8),
+                    _ => return Err(MyKeyringError::InvalidPercentEncoding),
+                }
+            }
+            b'+' if plus_as_space => decoded.push(b' '),
+            _ => decoded.push(byte),
+        }
+    }
+
+    String::from_utf8(decoded).map_err(|_| MyKeyringError::InvalidPercentEncoding)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uri_round_trip() -> crate::Result<()> {
+        let mut t = Totp::new("GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ", 8, 60, Algorithm::Sha512)?;
+        t.set_label(Some("My Issuer: Inc"), Some("john+doe@email.com"));
+
+        let parsed = Totp::from_uri(&t.to_uri())?;
+        assert_eq!(parsed.issuer(), Some("My Issuer: Inc"));
+        assert_eq!(parsed.account(), Some("john+doe@email.com"));
+        assert_eq!(parsed.digits, 8);
+        assert_eq!(parsed.period, 60);
+        assert_eq!(parsed.algorithm, Algorithm::Sha512);
+        assert_eq!(
+            parsed.totp_from_timestamp(1_234_567_890),
+            t.totp_from_timestamp(1_234_567_890)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn uri_steam_encoder() -> crate::Result<()> {
+        let t = Totp::from_uri(
+            "otpauth://totp/Steam:alice?secret=JBSWY3DPEHPK3PXP&issuer=Steam&encoder=steam",
+        )?;
+        assert_eq!(t.encoding(), Encoding::Steam);
+        assert_eq!(t.digits, 5);
+
+        let parsed = Totp::from_uri(&t.to_uri())?;
+        assert_eq!(parsed.encoding(), Encoding::Steam);
+        assert_eq!(
+            parsed.totp_from_timestamp(1_234_567_890),
+            t.totp_from_timestamp(1_234_567_890)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn uri_defaults() -> crate::Result<()> {
+        let t = Totp::from_uri("otpauth://totp/Example:alice@google.com?secret=JBSWY3DPEHPK3PXP")?;
+        assert_eq!(t.issuer(), Some("Example"));
+        assert_eq!(t.account(), Some("alice@google.com"));
+        assert_eq!(t.digits, 6);
+        assert_eq!(t.period, 30);
+        assert_eq!(t.algorithm, Algorithm::Sha1);
+
+        let t = Totp::from_uri("otpauth://totp/alice?secret=jbswy3dpehpk3pxp&algorithm=sha256")?;
+        assert_eq!(t.issuer(), None);
+        assert_eq!(t.account(), Some("alice"));
+        assert_eq!(t.algorithm, Algorithm::Sha256);
+
+        Ok(())
+    }
+
+    #[test]
+    fn uri_issuer_parameter_precedence() -> crate::Result<()> {
+        let t = Totp::from_uri(
+            "otpauth://totp/Old%3A%20alice?issuer=New+Issuer&secret=JBSWY3DPEHPK3PXP",
+        )?;
+        assert_eq!(t.issuer(), Some("New Issuer"));
+        assert_eq!(t.account(), Some("alice"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn uri_errors() {
+        let err = |uri| Totp::from_uri(uri).unwrap_err();
+
+        assert_eq!(
+            err("https://totp/a?secret=JBSWY3DPEHPK3PXP"),
+            MyKeyringError::InvalidUriScheme
+        );
+        assert_eq!(
+            err("otpauth://hotp/a?secret=JBSWY3DPEHPK3PXP&counter=1"),
+            MyKeyringError::UnsupportedOtpType
+        );
+        assert_eq!(err("otpauth://totp/a"), MyKeyringError::MissingSecret);
+        assert_eq!(
+            err("otpauth://totp/a?secret="),
+            MyKeyringError::MissingSecret
+        );
+        assert_eq!(
+            err("otpauth://totp/a?secret=JBSWY3DPEHPK3PX1"),
+            MyKeyringError::InvalidBase32
+        );
+        assert_eq!(
+            err("otpauth://totp/a?secret=JBSWY3DPEHPK3PXP&digits=0"),
+            MyKeyringError::InvalidUriParameter
+        );
+        assert_eq!(
+            err("otpauth://totp/a?secret=JBSWY3DPEHPK3PXP&period=-30"),
+            MyKeyringError::InvalidUriParameter
+        );
+        assert_eq!(
+            err("otpauth://totp/a?secret=JBSWY3DPEHPK3PXP&algorithm=MD5"),
+            MyKeyringError::InvalidUriParameter
+        );
+        assert_eq!(
+            err("otpauth://totp/a%2?secret=JBSWY3DPEHPK3PXP"),
+            MyKeyringError::InvalidPercentEncoding
+        );
+        assert_eq!(
+            err("otpauth://totp/a%FF?secret=JBSWY3DPEHPK3PXP"),
+            MyKeyringError::InvalidPercentEncoding
+        );
+    }
+}
@@ -0,0 +1,396 @@
+//! Encrypted container holding the keyring items
+//!
+//! The serialised vault starts with a clear header, made of a magic number,
+//! the format version, the parameters of the key derivation function and its
+//! salt. It is followed by the bincode serialised entries, encrypted as a
+//! [`CryptedMessage`] with the key derived from the password.
+//!
+//! # Examples
+//!
+//! ```
+//! # fn main() -> my_keyring_shared::Result<()> {
+//! use my_keyring_shared::{
+//!     crypt::Kdf,
+//!     totp::Totp,
+//!     vault::{Entry, Vault},
+//! };
+//!
+//! # let kdf = Kdf::Pbkdf2 { iterations: 1_000 };
+//! let mut vault = Vault::with_kdf(b"My_secr3tP@55w0rd", kdf)?;
+//! let id = vault.add(Entry::Totp(Totp::new(
+//!     "JBSWY3DPEB3W64TMMQQQ",
+//!     None,
+//!     None,
+//!     None,
+//! )?));
+//!
+//! // Serialize the vault to store it
+//! let data = vault.save()?;
+//!
+//! // Read it back
+//! let vault = Vault::open(&data, b"My_secr3tP@55w0rd")?;
+//! assert!(vault.get(id).is_some());
+//! # Ok(())
+//! # }
+//! ```
+
+use std::convert::TryFrom;
+
+use bincode::{deserialize, deserialize_from, serialize};
+use fnv::FnvHashMap;
+use serde::{Deserialize, Serialize};
+use ulid::Ulid;
+
+use crate::{
+    authentication::Authentication,
+    crypt::{crypt_with_key, decrypt_with_key, random_salt, CryptedMessage, Kdf, Key, Salt},
+    errors::MyKeyringError,
+    item::{ApiToken, CreditCard, Identity, Licence, SshKey, WifiNetwork},
+    note::Note,
+    secret::{Locked, SecretBytes},
+    tag::{TagPool, Tags},
+    totp::Totp,
+};
+
+/// Magic number at the beginning of every serialised vault
+const MAGIC: &[u8; 4] = b"MKRV";
+/// Current version of the vault format
+pub const VERSION: u8 = 1;
+/// Application specific context of the entries encryption
+const CONTEXT: &[u8] = b"my-keyring vault entries";
+
+/// An item stored in the vault
+#[derive(Debug, Serialize, Deserialize)]
+pub enum Entry {
+    /// Login information
+    Authentication(Authentication),
+    /// Free text
+    Note(Note),
+    /// One-time password generator
+    Totp(Totp),
+    /// Payment card
+    CreditCard(CreditCard),
+    /// Personal information
+    Identity(Identity),
+    /// SSH key pair
+    SshKey(SshKey),
+    /// Token giving access to an API
+    ApiToken(ApiToken),
+    /// Wi-Fi network
+    WifiNetwork(WifiNetwork),
+    /// Software licence
+    Licence(Licence),
+}
+
+impl Entry {
+    /// Return the item as [`Tags`], to tag it, none for the one-time password
+    /// generators, which cannot be tagged
+    pub fn as_tags_mut(&mut self) -> Option<&mut dyn Tags> {
+        match self {
+            Self::Authentication(item) => Some(item),
+            Self::Note(item) => Some(item),
+            Self::Totp(_) => None,
+            Self::CreditCard(item) => Some(item),
+            Self::Identity(item) => Some(item),
+            Self::SshKey(item) => Some(item),
+            Self::ApiToken(item) => Some(item),
+            Self::WifiNetwork(item) => Some(item),
+            Self::Licence(item) => Some(item),
+        }
+    }
+}
+
+/// Decrypted content of the vault
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Content {
+    /// Tags that can be associated with the entries
+    tags: TagPool,
+    /// Entries, by their identifier
+    entries: FnvHashMap<Ulid, Entry>,
+}
+
+/// Encrypted container of [`Entry`] and their [`TagPool`]
+pub struct Vault {
+    /// Key derivation function parameters
+    kdf: Kdf,
+    /// Salt of the key derivation function
+    salt: Salt,
+    /// Key derived from the password, locked in memory if possible
+    key: Locked<Key>,
+    /// Decrypted content
+    content: Content,
+}
+
+opaque_debug::implement!(Vault);
+
+impl Vault {
+    /// Create an empty vault, protected by the `password`, using the default
+    /// key derivation function
+    pub fn new(password: &[u8]) -> Self {
+        Self::with_kdf(password, Kdf::default()).expect("valid default kdf")
+    }
+
+    /// Create an empty vault, protected by the `password`, using the `kdf` key
+    /// derivation function
+    ///
+    /// # Errors
+    ///
+    /// Return [`MyKeyringError::InvalidKdfParameters`] if the `kdf`
+    /// parameters are out of range
+    pub fn with_kdf(password: &[u8], kdf: Kdf) -> crate::Result<Self> {
+        let salt = random_salt();
+
+        Ok(Self {
+            kdf,
+            salt,
+            key: kdf.derive(password, &salt)?,
+            content: Content::default(),
+        })
+    }
+
+    /// Read and decrypt a vault previously serialised with [`Vault::save`]
+    ///
+    /// # Errors
+    ///
+    /// Return [`MyKeyringError::InvalidVault`] if `data` is not a vault,
+    /// [`MyKeyringError::UnsupportedVaultVersion`] if the vault was created by
+    /// a newer format, [`MyKeyringError::InvalidKdfParameters`] if the header
+    /// parameters are out of range, and [`MyKeyringError::IncorrectHmac`] if
+    /// the `password` is wrong or the vault has been altered
+    pub fn open(data: &[u8], password: &[u8]) -> crate::Result<Self> {
+        if data.len() < MAGIC.len() + 1 || &data[..MAGIC.len()] != MAGIC {
+            return Err(MyKeyringError::InvalidVault);
+        }
+        let version = data[MAGIC.len()];
+        if version != VERSION {
+            return Err(MyKeyringError::UnsupportedVaultVersion);
+        }
+
+        // Header, the reader is advanced up to the encrypted entries
+        let mut reader = &data[MAGIC.len() + 1..];
+        let (kdf, salt): (Kdf, Salt) =
+            deserialize_from(&mut reader).map_err(|_| MyKeyringError::InvalidVault)?;
+        let encrypted = CryptedMessage::try_from(reader)?;
+
+        let key = kdf.derive(password, &salt)?;
+        let content = decrypt_with_key(&key[..], encrypted, Some(CONTEXT), None)?;
+        let content = deserialize(&content).map_err(|_| MyKeyringError::InvalidVault)?;
+
+        Ok(Self {
+            kdf,
+            salt,
+            key,
+            content,
+        })
+    }
+
+    /// Check that the key derived from the password is locked in memory
+    ///
+    /// # Errors
+    ///
+    /// Return [`MyKeyringError::MemoryNotLocked`] if it may be swapped to disk
+    #[inline]
+    pub fn ensure_locked(&self) -> crate::Result<()> {
+        self.key.ensure_locked()
+    }
+
+    /// Encrypt and serialise the vault, to store it
+    ///
+    /// # Errors
+    ///
+    /// Return [`MyKeyringError::DataLengthExceeded`] if the entries are too big
+    /// to be encrypted
+    pub fn save(&self) -> crate::Result<Vec<u8>> {
+        let mut data = MAGIC.to_vec();
+        data.push(VERSION);
+        data.extend(serialize(&(self.kdf, self.salt)).expect("serialized vault header"));
+
+        let content =
+            SecretBytes::from(serialize(&self.content).expect("serialized vault content"));
+        let encrypted: Vec<u8> =
+            crypt_with_key(&self.key[..], None, &content, Some(CONTEXT), None)?.into();
+        data.extend(encrypted);
+
+        Ok(data)
+    }
+
+    /// Return the key derivation function parameters
+    #[inline]
+    pub fn kdf(&self) -> Kdf {
+        self.kdf
+    }
+
+    /// Add an `entry`, returning its newly generated identifier
+    pub fn add(&mut self, entry: Entry) -> Ulid {
+        let id = Ulid::new();
+        self.content.entries.insert(id, entry);
+        id
+    }
+
+    /// Remove the entry `id`, returning it if it was present
+    pub fn remove(&mut self, id: Ulid) -> Option<Entry> {
+        self.content.entries.remove(&id)
+    }
+
+    /// Return the entry `id`
+    pub fn get(&self, id: Ulid) -> Option<&Entry> {
+        self.content.entries.get(&id)
+    }
+
+    /// Return the entry `id`, to modify it
+    pub fn get_mut(&mut self, id: Ulid) -> Option<&mut Entry> {
+        self.content.entries.get_mut(&id)
+    }
+
+    /// List all the entries, with their identifiers, in no particular order
+    pub fn list(&self) -> impl Iterator<Item = (Ulid, &Entry)> {
+        self.content.entries.iter().map(|(id, entry)| (*id, entry))
+    }
+
+    /// Return the tags of the vault
+    #[inline]
+    pub fn tags(&self) -> &TagPool {
+        &self.content.tags
+    }
+
+    /// Return the tags of the vault, to modify them
+    #[inline]
+    pub fn tags_mut(&mut self) -> &mut TagPool {
+        &mut self.content.tags
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PASSWORD: &[u8] = b"My_secr3tP@55w0rd";
+    const KDF: Kdf = Kdf::Pbkdf2 { iterations: 10 };
+
+    fn vault() -> crate::Result<(Vault, Ulid, Ulid)> {
+        let mut vault = Vault::with_kdf(PASSWORD, KDF)?;
+        let auth = vault.add(Entry::Authentication(Authentication::new(
+            "Website", "john", "p@55", "",
+        )));
+        let note = vault.add(Entry::Note(Note::new("Lorem ipsum")));
+        vault.add(Entry::Totp(Totp::new(
+            "JBSWY3DPEB3W64TMMQQQ",
+            None,
+            None,
+            None,
+        )?));
+        vault.add(Entry::CreditCard(CreditCard::new(
+            "John Doe",
+            "4111 1111 1111 1111",
+            2030,
+            12,
+        )?));
+        vault.add(Entry::Licence(Licence::new(
+            "Editor",
+            "John Doe",
+            "ABCD-1234",
+        )));
+        vault.tags_mut().add_tag("Work");
+
+        Ok((vault, auth, note))
+    }
+
+    #[test]
+    fn save_then_open() -> crate::Result<()> {
+        let (vault, auth, note) = vault()?;
+
+        let opened = Vault::open(&vault.save()?, PASSWORD)?;
+        assert_eq!(opened.kdf(), KDF);
+        assert_eq!(opened.list().count(), 5);
+        assert!(matches!(opened.get(auth), Some(Entry::Authentication(_))));
+        assert!(matches!(opened.get(note), Some(Entry::Note(_))));
+        assert!(opened.tags().get_tag_id("Work").is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn tag_entries() -> crate::Result<()> {
+        let (mut vault, auth, _) = vault()?;
+        let work = vault.tags().get_tag_id("Work").unwrap();
+
+        let entries: Vec<Ulid> = vault.list().map(|(id, _)| id).collect();
+        let mut tagged = 0;
+        for id in entries {
+            if let Some(item) = vault.get_mut(id).unwrap().as_tags_mut() {
+                item.add_tag(work);
+                tagged += 1;
+            }
+        }
+        // All but the Totp
+        assert_eq!(tagged, 4);
+
+        let opened = Vault::open(&vault.save()?, PASSWORD)?;
+        match opened.get(auth) {
+            Some(Entry::Authentication(auth)) => assert_eq!(auth.tag_ids(), [work]),
+            _ => panic!("not an authentication"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn add_and_remove() -> crate::Result<()> {
+        let (mut vault, auth, _) = vault()?;
+
+        assert!(matches!(vault.remove(auth), Some(Entry::Authentication(_))));
+        assert!(vault.remove(auth).is_none());
+        assert!(vault.get(auth).is_none());
+
+        let opened = Vault::open(&vault.save()?, PASSWORD)?;
+        assert_eq!(opened.list().count(), 4);
+        assert!(opened.get(auth).is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn wrong_password() -> crate::Result<()> {
+        let (vault, ..) = vault()?;
+
+        let wrong = Vault::open(&vault.save()?, b"password");
+        assert_eq!(wrong.unwrap_err(), MyKeyringError::IncorrectHmac);
+
+        Ok(())
+    }
+
+    #[test]
+    fn altered_header() -> crate::Result<()> {
+        let (vault, ..) = vault()?;
+        let data = vault.save()?;
+
+        assert_eq!(
+            Vault::open(&data[..3], PASSWORD).unwrap_err(),
+            MyKeyringError::InvalidVault
+        );
+
+        let mut altered = data.clone();
+        altered[0] ^= 1;
+        assert_eq!(
+            Vault::open(&altered, PASSWORD).unwrap_err(),
+            MyKeyringError::InvalidVault
+        );
+
+        let mut altered = data.clone();
+        altered[MAGIC.len()] = VERSION + 1;
+        assert_eq!(
+            Vault::open(&altered, PASSWORD).unwrap_err(),
+            MyKeyringError::UnsupportedVaultVersion
+        );
+
+        // Changing the salt gives another key
+        let mut altered = data;
+        altered[MAGIC.len() + 10] ^= 1;
+        assert_eq!(
+            Vault::open(&altered, PASSWORD).unwrap_err(),
+            MyKeyringError::IncorrectHmac
+        );
+
+        Ok(())
+    }
+}